@@ -29,6 +29,45 @@ pub enum Error {
         /// The given invalid insertion index.
         actual_index: usize,
     },
+
+    /// The given indices are not pairwise distinct.
+    #[error(
+        "the given indices are not pairwise distinct, as the index {index} occurs more than once"
+    )]
+    OverlappingIndices {
+        /// The index that occurs more than once.
+        index: usize,
+    },
+
+    /// The given index exceeds the maximum supported index.
+    #[error("the given index {index} exceeds the maximum supported index {max_index}")]
+    IndexOutOfBounds {
+        /// The index.
+        index: usize,
+        /// The maximum supported index.
+        max_index: usize,
+    },
+
+    /// The stable vector already contains its maximum number of elements.
+    #[error("the stable vector already contains its maximum number of {max} elements")]
+    CapacityExceeded {
+        /// The maximum number of elements.
+        max: usize,
+    },
+
+    /// The memory for the given number of slots could not be allocated.
+    #[error("the memory for {len} slots could not be allocated")]
+    AllocationFailed {
+        /// The number of slots.
+        len: usize,
+    },
+
+    /// An internal invariant of the stable vector is violated.
+    #[error("an internal invariant of the stable vector is violated: {description}")]
+    InvariantViolation {
+        /// A description of the violated invariant.
+        description: String,
+    },
 }
 
 /// A shortcut result type using this crate's error type.
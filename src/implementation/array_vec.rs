@@ -0,0 +1,487 @@
+//! A stable vector based on a fixed-size array.
+//!
+//! Each element is stored as an `Option` in an array of `N` slots, and a fixed-size free list is used to keep track of "holes" in the array.
+//! This allows O(1) insertions and deletions without any heap allocation, but limits the number of elements to `N`.
+
+use std::{
+    array,
+    fmt::Debug,
+    iter::{self, FusedIterator},
+    marker::PhantomData,
+};
+
+use crate::{
+    error::Error,
+    interface::{
+        debug_assert_lossless_index, StableVec, StableVecAccess, StableVecIndex,
+        StableVecReadAccess,
+    },
+};
+
+use super::option_vec::get_disjoint_slots_mut;
+
+/// A stable vector based on a fixed-size array of `N` slots with a fixed-size free list.
+///
+/// Each element is stored as an `Option`, and a free list is used to keep track of "holes" in the array.
+/// This allows O(1) insertions and deletions without any heap allocation.
+///
+/// Since the array cannot grow, at most `N` elements can be stored.
+/// Insertions beyond that fail with an [`Error::CapacityExceeded`],
+/// or panic if the inserting method is infallible, like [`insert`](StableVec::insert).
+/// Insertions at an index of `N` or more fail with an [`Error::IndexOutOfBounds`], even if the array is not full.
+/// Use [`try_insert`](ArrayStableVec::try_insert) and [`try_insert_in_place`](ArrayStableVec::try_insert_in_place) to handle this case.
+pub struct ArrayStableVec<Data, Index, const N: usize> {
+    slots: [Option<Data>; N],
+    free_list: [usize; N],
+    free_list_len: usize,
+    used_len: usize,
+    phantom_data: PhantomData<Index>,
+}
+
+impl<Data, Index, const N: usize> ArrayStableVec<Data, Index, N> {
+    /// Create a new empty [`ArrayStableVec`].
+    pub fn new() -> Self {
+        Self {
+            slots: array::from_fn(|_| None),
+            free_list: [0; N],
+            free_list_len: 0,
+            used_len: 0,
+            phantom_data: Default::default(),
+        }
+    }
+
+    /// Return the index at which the next element would be inserted, or an [`Error::CapacityExceeded`] if the array is full.
+    fn next_insertion_index(&self) -> crate::error::Result<usize> {
+        if self.free_list_len > 0 {
+            Ok(self.free_list[self.free_list_len - 1])
+        } else if self.used_len < N {
+            Ok(self.used_len)
+        } else {
+            Err(Error::CapacityExceeded { max: N })
+        }
+    }
+
+    /// Mark the given index as used by removing it from the free list or growing the used part of the array.
+    /// The index must not be in use.
+    fn claim_index(&mut self, index: usize) {
+        if index >= self.used_len {
+            for free_index in self.used_len..index {
+                self.free_list[self.free_list_len] = free_index;
+                self.free_list_len += 1;
+            }
+            self.used_len = index + 1;
+        } else {
+            let position = self.free_list[..self.free_list_len]
+                .iter()
+                .position(|&free_index| free_index == index)
+                .unwrap();
+            self.free_list
+                .copy_within(position + 1..self.free_list_len, position);
+            self.free_list_len -= 1;
+        }
+    }
+}
+
+impl<Data, Index: StableVecIndex, const N: usize> ArrayStableVec<Data, Index, N> {
+    /// Insert a single element into the stable vector at an arbitrary index.
+    /// Return the index, or an [`Error::CapacityExceeded`] if the array is full.
+    pub fn try_insert(&mut self, element: Data) -> crate::error::Result<Index> {
+        self.try_insert_in_place(|_| element)
+    }
+
+    /// Insert a single element into the stable vector by constructing it in place.
+    /// Return the index, or an [`Error::CapacityExceeded`] if the array is full.
+    /// In the latter case, the constructor is not called.
+    pub fn try_insert_in_place(
+        &mut self,
+        constructor: impl FnOnce(Index) -> Data,
+    ) -> crate::error::Result<Index> {
+        let index = self.next_insertion_index()?;
+        debug_assert_lossless_index::<Index>(index);
+        let element = constructor(index.into());
+        self.claim_index(index);
+        self.slots[index] = Some(element);
+        Ok(index.into())
+    }
+}
+
+impl<Data, Index: StableVecIndex, const N: usize> StableVec<Data, Index>
+    for ArrayStableVec<Data, Index, N>
+{
+    fn insert(&mut self, element: Data) -> Index {
+        self.try_insert(element).unwrap()
+    }
+
+    fn insert_ref(&mut self, element: Data) -> (Index, &mut Data) {
+        let index: usize = self.insert(element).into();
+        (index.into(), self.slots[index].as_mut().unwrap())
+    }
+
+    fn insert_in_place(&mut self, constructor: impl FnOnce(Index) -> Data) -> Index {
+        self.try_insert_in_place(constructor).unwrap()
+    }
+
+    fn insert_at(&mut self, index: Index, element: Data) -> crate::error::Result<()> {
+        let expected_index = self.next_insertion_index()?;
+        let index = index.into();
+        if expected_index == index {
+            let inserted_index = self.try_insert(element)?;
+            assert_eq!(inserted_index.into(), index);
+            Ok(())
+        } else {
+            Err(Error::NotTheNextAvailableInsertionIndex {
+                expected_index,
+                actual_index: index,
+            })
+        }
+    }
+
+    fn insert_at_arbitrary_index(
+        &mut self,
+        index: Index,
+        element: Data,
+    ) -> crate::error::Result<()> {
+        self.insert_in_place_at(index, |_| element)
+    }
+
+    fn insert_in_place_at(
+        &mut self,
+        index: Index,
+        constructor: impl FnOnce(Index) -> Data,
+    ) -> crate::error::Result<()> {
+        let index = index.into();
+        debug_assert_lossless_index::<Index>(index);
+        if index >= N {
+            return Err(match N.checked_sub(1) {
+                Some(max_index) => Error::IndexOutOfBounds { index, max_index },
+                None => Error::CapacityExceeded { max: N },
+            });
+        }
+        if self.slots[index].is_some() {
+            return Err(Error::IndexAlreadyInUse { index });
+        }
+
+        let element = constructor(index.into());
+        self.claim_index(index);
+        self.slots[index] = Some(element);
+        Ok(())
+    }
+
+    fn remove(&mut self, index: Index) -> crate::error::Result<Data> {
+        let index = index.into();
+        self.take(index.into())
+            .ok_or(Error::UnmappedIndex { index })
+    }
+
+    fn take(&mut self, index: Index) -> Option<Data> {
+        let index = index.into();
+        let element = self.slots.get_mut(index)?.take()?;
+        self.free_list[self.free_list_len] = index;
+        self.free_list_len += 1;
+        Some(element)
+    }
+
+    /// Returns an iterator that iterates over the available insertion indices in this stable vector.
+    /// These are the "holes" in the array, followed by the unused indices at the end of the array.
+    ///
+    /// Unlike for growable stable vectors, this iterator is finite, as there are only `N` indices.
+    fn available_insertion_index_iterator<'result>(&self) -> impl 'result + Iterator<Item = Index>
+    where
+        Index: 'result,
+    {
+        let free_list = self.free_list;
+        free_list
+            .into_iter()
+            .take(self.free_list_len)
+            .rev()
+            .chain(self.used_len..N)
+            .map(Into::into)
+    }
+
+    fn iter<'this>(&'this self) -> impl 'this + FusedIterator<Item = (Index, &'this Data)>
+    where
+        Data: 'this,
+    {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, element)| element.as_ref().map(|element| (index.into(), element)))
+    }
+
+    fn iter_mut<'this>(
+        &'this mut self,
+    ) -> impl 'this + FusedIterator<Item = (Index, &'this mut Data)>
+    where
+        Data: 'this,
+    {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, element)| element.as_mut().map(|element| (index.into(), element)))
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(&Data) -> bool) {
+        for index in 0..self.used_len {
+            if let Some(element) = self.slots[index].as_ref() {
+                if !f(element) {
+                    self.remove(index.into()).unwrap();
+                }
+            }
+        }
+    }
+
+    fn compact_stable(&mut self) -> Vec<(Index, Index)> {
+        let mut remapping = Vec::with_capacity(self.len());
+        for index in 0..self.used_len {
+            if let Some(element) = self.slots[index].take() {
+                let new_index = remapping.len();
+                self.slots[new_index] = Some(element);
+                remapping.push((index.into(), new_index.into()));
+            }
+        }
+        self.used_len = remapping.len();
+        self.free_list_len = 0;
+        remapping
+    }
+
+    fn clear(&mut self) {
+        self.slots = array::from_fn(|_| None);
+        self.free_list_len = 0;
+        self.used_len = 0;
+    }
+}
+
+impl<Data, Index: StableVecIndex, const N: usize> StableVecReadAccess<Data, Index>
+    for ArrayStableVec<Data, Index, N>
+{
+    fn get(&self, index: Index) -> crate::error::Result<&Data> {
+        let index = index.into();
+        debug_assert_lossless_index::<Index>(index);
+        match self.slots.get(index) {
+            Some(Some(element)) => Ok(element),
+            _ => Err(Error::UnmappedIndex { index }),
+        }
+    }
+
+    fn is_occupied(&self, index: Index) -> bool {
+        matches!(self.slots.get(index.into()), Some(Some(_)))
+    }
+
+    fn len(&self) -> usize {
+        self.used_len - self.free_list_len
+    }
+}
+
+impl<Data, Index: StableVecIndex, const N: usize> StableVecAccess<Data, Index>
+    for ArrayStableVec<Data, Index, N>
+{
+    fn get_mut(&mut self, index: Index) -> crate::error::Result<&mut Data> {
+        let index = index.into();
+        debug_assert_lossless_index::<Index>(index);
+        match self.slots.get_mut(index) {
+            Some(Some(element)) => Ok(element),
+            _ => Err(Error::UnmappedIndex { index }),
+        }
+    }
+
+    fn get_disjoint_mut_opt<const M: usize>(
+        &mut self,
+        indices: [Index; M],
+    ) -> crate::error::Result<[Option<&mut Data>; M]> {
+        get_disjoint_slots_mut(&mut self.slots, indices.map(Into::into))
+    }
+}
+
+impl<Data, Index, const N: usize> Default for ArrayStableVec<Data, Index, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Data: Clone, Index, const N: usize> Clone for ArrayStableVec<Data, Index, N> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            free_list: self.free_list,
+            free_list_len: self.free_list_len,
+            used_len: self.used_len,
+            phantom_data: self.phantom_data,
+        }
+    }
+}
+
+/// Panics if the vector contains more than `N` elements.
+impl<Data, Index, const N: usize> From<Vec<Data>> for ArrayStableVec<Data, Index, N> {
+    fn from(value: Vec<Data>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+impl<Data, Index, const N: usize> IntoIterator for ArrayStableVec<Data, Index, N> {
+    type Item = Data;
+    type IntoIter = iter::Flatten<array::IntoIter<Option<Data>, N>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slots.into_iter().flatten()
+    }
+}
+
+/// Panics if the iterator yields more than `N` elements.
+impl<Data, Index, const N: usize> FromIterator<Data> for ArrayStableVec<Data, Index, N> {
+    fn from_iter<T: IntoIterator<Item = Data>>(iter: T) -> Self {
+        let mut result = Self::new();
+        for element in iter {
+            assert!(
+                result.used_len < N,
+                "{}",
+                Error::CapacityExceeded { max: N }
+            );
+            result.slots[result.used_len] = Some(element);
+            result.used_len += 1;
+        }
+        result
+    }
+}
+
+impl<Data: Debug, Index: StableVecIndex + Debug, const N: usize> Debug
+    for ArrayStableVec<Data, Index, N>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ArrayStableVec [")?;
+
+        let mut once = false;
+        for (index, element) in self.slots.iter().enumerate() {
+            let Some(element) = element else { continue };
+            if once {
+                write!(f, ", ")?;
+            } else {
+                once = true;
+            }
+            let index: Index = index.into();
+            write!(f, "({index:?}, {element:?})")?;
+        }
+
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Error,
+        interface::{StableVec, StableVecAccess, StableVecReadAccess},
+    };
+
+    use super::ArrayStableVec;
+
+    #[test]
+    fn compact_stable_assigns_new_indices_in_ascending_order_of_old_indices() {
+        let mut vec: ArrayStableVec<char, usize, 8> = "abcdefg".chars().collect();
+        for index in [0, 2, 3, 6] {
+            vec.remove(index).unwrap();
+        }
+
+        assert_eq!(vec.compact_stable(), [(1, 0), (4, 1), (5, 2)]);
+        assert_eq!(
+            vec.iter().collect::<Vec<_>>(),
+            [(0, &'b'), (1, &'e'), (2, &'f')]
+        );
+        assert_eq!(vec.insert('h'), 3);
+        assert_eq!(vec.compact_stable(), [(0, 0), (1, 1), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn reserve_index_range_fills_only_free_indices() {
+        let mut vec: ArrayStableVec<u32, usize, 8> = (1..=3).collect();
+        vec.remove(1).unwrap();
+
+        vec.reserve_index_range(0..5);
+        assert_eq!(
+            vec.iter().collect::<Vec<_>>(),
+            [(0, &1), (1, &0), (2, &3), (3, &0), (4, &0)]
+        );
+        assert_eq!(vec.insert(5), 5);
+    }
+
+    #[test]
+    fn free_list_reuses_the_most_recently_freed_index_first() {
+        let mut vec: ArrayStableVec<u32, usize, 6> = (0..6).collect();
+        for index in [1, 4, 2] {
+            vec.remove(index).unwrap();
+        }
+
+        assert_eq!([vec.insert(10), vec.insert(11), vec.insert(12)], [2, 4, 1]);
+        assert_eq!(vec.len(), 6);
+    }
+
+    #[test]
+    fn insertions_fail_once_the_array_is_full() {
+        let mut vec: ArrayStableVec<u32, usize, 4> = (0..4).collect();
+        assert!(matches!(
+            vec.try_insert(4),
+            Err(Error::CapacityExceeded { max: 4 })
+        ));
+        assert!(matches!(
+            vec.insert_at_arbitrary_index(2, 2),
+            Err(Error::IndexAlreadyInUse { index: 2 })
+        ));
+        assert!(matches!(
+            vec.insert_in_place_at(4, |_| unreachable!()),
+            Err(Error::IndexOutOfBounds {
+                index: 4,
+                max_index: 3
+            })
+        ));
+
+        vec.remove(2).unwrap();
+        assert_eq!(vec.try_insert(5).unwrap(), 2);
+        assert!(vec.try_insert(6).is_err());
+    }
+
+    #[test]
+    fn insert_at_arbitrary_index_frees_the_skipped_indices() {
+        let mut vec: ArrayStableVec<u32, usize, 4> = ArrayStableVec::new();
+        vec.insert_at_arbitrary_index(2, 2).unwrap();
+        assert_eq!(
+            vec.available_insertion_index_iterator().collect::<Vec<_>>(),
+            [1, 0, 3]
+        );
+        assert_eq!([vec.insert(1), vec.insert(0), vec.insert(3)], [1, 0, 3]);
+        assert!(vec.try_insert(4).is_err());
+    }
+
+    #[test]
+    fn get_disjoint_mut_opt_skips_holes_and_rejects_overlapping_indices() {
+        let mut vec: ArrayStableVec<u32, usize, 4> = (0..3).collect();
+        vec.remove(1).unwrap();
+
+        let [first, hole, beyond, last] = vec.get_disjoint_mut_opt([0, 1, 7, 2]).unwrap();
+        assert_eq!(
+            (first, hole, beyond, last),
+            (Some(&mut 0), None, None, Some(&mut 2))
+        );
+        assert!(matches!(
+            vec.get_disjoint_mut_opt([2, 0, 2]),
+            Err(Error::OverlappingIndices { index: 2 })
+        ));
+    }
+
+    #[test]
+    fn insertions_beyond_the_array_are_out_of_bounds() {
+        let mut vec: ArrayStableVec<u32, usize, 4> = ArrayStableVec::new();
+        assert!(matches!(
+            vec.insert_at_arbitrary_index(7, 7),
+            Err(Error::IndexOutOfBounds {
+                index: 7,
+                max_index: 3
+            })
+        ));
+        assert!(vec.is_empty());
+
+        let mut vec: ArrayStableVec<u32, usize, 0> = ArrayStableVec::new();
+        assert!(matches!(
+            vec.insert_at_arbitrary_index(0, 0),
+            Err(Error::CapacityExceeded { max: 0 })
+        ));
+    }
+}
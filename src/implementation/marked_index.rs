@@ -7,12 +7,23 @@ use crate::interface::StableVecIndex;
 
 /// An index type that is marked by a type `Marker`.
 /// This prevents to accidentally use a wrong value as the index for a stable vector.
-#[derive(Debug)]
 pub struct MarkedIndex<Marker> {
     index: usize,
     marker: PhantomData<Marker>,
 }
 
+impl<Marker> MarkedIndex<Marker> {
+    /// Create a marked index from the given raw index.
+    pub fn from_raw(index: usize) -> Self {
+        index.into()
+    }
+
+    /// Return the raw index.
+    pub fn as_usize(&self) -> usize {
+        self.index
+    }
+}
+
 impl<Marker> StableVecIndex for MarkedIndex<Marker> {}
 
 impl<Marker> From<usize> for MarkedIndex<Marker> {
@@ -58,6 +69,15 @@ impl<Marker> Ord for MarkedIndex<Marker> {
     }
 }
 
+impl<Marker> core::fmt::Debug for MarkedIndex<Marker> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MarkedIndex")
+            .field("index", &self.index)
+            .field("marker", &self.marker)
+            .finish()
+    }
+}
+
 impl<Marker> core::hash::Hash for MarkedIndex<Marker> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.index.hash(state);
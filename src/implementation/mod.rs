@@ -1,5 +1,9 @@
 //! Various implementations of stable vector types and index types.
 
+pub mod array_vec;
 pub mod marked_index;
+pub mod non_zero_u32_index;
 pub mod option_vec;
+#[cfg(target_pointer_width = "64")]
+pub mod packed_index;
 pub mod usize_index;
@@ -0,0 +1,44 @@
+//! A 32-bit index type with a niche, such that `Option<NonZeroU32Index>` is as large as `u32`.
+
+use std::{fmt::Debug, num::NonZeroU32};
+
+use crate::interface::StableVecIndex;
+
+/// A 32-bit index type that stores `index + 1` in a [`NonZeroU32`].
+/// This makes `Option<NonZeroU32Index>` as large as `u32`.
+///
+/// The largest supported index is `u32::MAX - 1`.
+/// Converting a larger `usize` into this type panics.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NonZeroU32Index {
+    index_plus_one: NonZeroU32,
+}
+
+impl StableVecIndex for NonZeroU32Index {
+    const MAX_INDEX: usize = u32::MAX as usize - 1;
+}
+
+impl From<usize> for NonZeroU32Index {
+    fn from(index: usize) -> Self {
+        let index_plus_one = index
+            .checked_add(1)
+            .and_then(|index_plus_one| u32::try_from(index_plus_one).ok())
+            .and_then(NonZeroU32::new)
+            .unwrap_or_else(|| panic!("index {index} is too large for a NonZeroU32Index"));
+        Self { index_plus_one }
+    }
+}
+
+impl From<NonZeroU32Index> for usize {
+    fn from(value: NonZeroU32Index) -> Self {
+        (value.index_plus_one.get() - 1) as usize
+    }
+}
+
+impl Debug for NonZeroU32Index {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("NonZeroU32Index")
+            .field(&usize::from(*self))
+            .finish()
+    }
+}
@@ -3,16 +3,39 @@
 //! Each element is stored as an `Option`, and a free list is used to keep track of "holes" in the vector.
 //! This allows amortised O(1) insertions and deletions, with a memory usage of O(|maximum len|).
 
-use std::{fmt::Debug, iter, marker::PhantomData, vec};
+use std::{
+    array,
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::Debug,
+    iter::{self, FusedIterator},
+    marker::PhantomData,
+    mem,
+    ops::{Bound, Range, RangeBounds},
+    vec,
+};
 
 use crate::{
     error::Error,
-    interface::{StableVec, StableVecAccess, StableVecIndex},
+    interface::{
+        debug_assert_lossless_index, StableVec, StableVecAccess, StableVecIndex,
+        StableVecReadAccess,
+    },
 };
 
 pub use available_insertion_index_iterator::AvailableInsertionIndexIterator;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use observed::ObservedStableVec;
+pub use subset_view::SubsetView;
 
 mod available_insertion_index_iterator;
+mod entry;
+mod observed;
+#[cfg(feature = "serde")]
+mod serialization;
+mod subset_view;
+#[cfg(test)]
+mod tests;
 
 /// A stable vector based on the [`Option`] type with a free list.
 ///
@@ -21,50 +44,1126 @@ mod available_insertion_index_iterator;
 pub struct OptionStableVec<Data, Index> {
     vec: Vec<Option<Data>>,
     free_list: Vec<usize>,
+    max_len: usize,
+    growth_policy: GrowthPolicy,
+    shrink_policy: ShrinkPolicy,
+    sorted_free_list: bool,
     phantom_data: PhantomData<Index>,
 }
 
+/// The strategy by which the underlying vector of an [`OptionStableVec`] grows its capacity when it is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GrowthPolicy {
+    /// Grow like a [`Vec`], i.e. by doubling the capacity, which gives amortised O(1) insertions.
+    #[default]
+    Amortized,
+    /// Grow the capacity by exactly the given number of slots, or as much as needed if that is more.
+    /// This wastes less memory, at the cost of more frequent reallocations.
+    Linear {
+        /// The number of slots to grow by.
+        chunk_size: usize,
+    },
+}
+
+/// The strategy by which an [`OptionStableVec`] releases unused memory after elements are removed.
+///
+/// The policy is applied after [`remove`](StableVec::remove), [`take`](StableVec::take) and [`retain`](StableVec::retain),
+/// but not while the iterator returned by [`remove_range`](OptionStableVec::remove_range) is consumed.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ShrinkPolicy {
+    /// Never release memory automatically, like a [`Vec`].
+    #[default]
+    Never,
+    /// Remove the holes at the end of the underlying vector and shrink its capacity to fit
+    /// once the [`load_factor`](OptionStableVec::load_factor) falls below the given threshold.
+    ///
+    /// Since the underlying vector doubles its capacity when it grows again, the threshold should be well below `0.5`,
+    /// otherwise alternating insertions and removals may reallocate every time.
+    BelowLoadFactor {
+        /// The load factor below which memory is released.
+        threshold: f64,
+    },
+}
+
+/// A summary of the internal state of an [`OptionStableVec`], as returned by [`OptionStableVec::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StableVecStats {
+    /// The number of elements.
+    pub len: usize,
+    /// The number of holes in the underlying vector.
+    pub hole_count: usize,
+    /// One more than the largest index that was ever used, i.e. the length of the underlying vector.
+    pub index_bound: usize,
+    /// The capacity of the underlying vector.
+    pub vec_capacity: usize,
+    /// The capacity of the free list.
+    pub free_list_capacity: usize,
+}
+
+/// Compute the capacity of the underlying vector of an [`OptionStableVec`] for a workload with at most `live` elements and `expected_holes` holes at the same time.
+///
+/// Every element and every hole occupies one slot of the underlying vector, so this is their sum.
+/// The free list is not accounted for, as it is a separate allocation that only holds the indices of the holes.
+pub const fn recommended_capacity(live: usize, expected_holes: usize) -> usize {
+    live.saturating_add(expected_holes)
+}
+
 impl<Data, Index> OptionStableVec<Data, Index> {
     /// Create a new empty [`OptionStableVec`].
     pub fn new() -> Self {
         Self {
             vec: Default::default(),
             free_list: Default::default(),
+            max_len: usize::MAX,
+            growth_policy: Default::default(),
+            shrink_policy: Default::default(),
+            sorted_free_list: false,
             phantom_data: Default::default(),
         }
     }
+
+    /// Create a new empty [`OptionStableVec`] whose underlying vector has at least the given capacity.
+    ///
+    /// See [`recommended_capacity`] for computing the capacity for a known workload.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            vec: Vec::with_capacity(capacity),
+            ..Self::new()
+        }
+    }
+
+    /// Create a new empty [`OptionStableVec`] whose underlying vector and free list have at least the given capacities.
+    ///
+    /// The free list holds the indices of the holes, so its capacity should be the maximum number of holes expected at the same time.
+    pub fn with_capacities(vec_capacity: usize, free_list_capacity: usize) -> Self {
+        Self {
+            vec: Vec::with_capacity(vec_capacity),
+            free_list: Vec::with_capacity(free_list_capacity),
+            ..Self::new()
+        }
+    }
+
+    /// Create a new empty [`OptionStableVec`] that holds at most `max` elements.
+    ///
+    /// This is a shortcut for `OptionStableVec::new().max_len(max)`, see [`max_len`](OptionStableVec::max_len).
+    pub fn with_max_len(max: usize) -> Self {
+        Self::new().max_len(max)
+    }
+
+    /// Create a new empty [`OptionStableVec`] whose underlying vector grows according to the given policy.
+    ///
+    /// This is a shortcut for `OptionStableVec::new().growth_policy(growth_policy)`.
+    pub fn with_growth_policy(growth_policy: GrowthPolicy) -> Self {
+        Self::new().growth_policy(growth_policy)
+    }
+
+    /// Create a new empty [`OptionStableVec`] that releases unused memory after removals according to the given policy.
+    ///
+    /// This is a shortcut for `OptionStableVec::new().shrink_policy(shrink_policy)`.
+    pub fn with_shrink_policy(shrink_policy: ShrinkPolicy) -> Self {
+        Self::new().shrink_policy(shrink_policy)
+    }
+
+    /// Create a new empty [`OptionStableVec`] whose insertions always reuse the smallest free index first.
+    ///
+    /// This is a shortcut for `OptionStableVec::new().sorted_free_list()`, see [`sorted_free_list`](OptionStableVec::sorted_free_list).
+    pub fn with_sorted_free_list() -> Self {
+        Self::new().sorted_free_list()
+    }
+
+    /// Limit this stable vector to at most `max` elements.
+    ///
+    /// Once the stable vector contains `max` elements, further insertions fail with an [`Error::CapacityExceeded`],
+    /// or panic if the inserting method is infallible, like [`insert`](StableVec::insert).
+    /// Use [`try_insert`](OptionStableVec::try_insert) and [`try_insert_in_place`](OptionStableVec::try_insert_in_place) to handle this case.
+    ///
+    /// Like the other configuration methods, this consumes and returns the stable vector, such that they can be chained:
+    /// `OptionStableVec::with_capacity(16).max_len(16).sorted_free_list()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stable vector already contains more than `max` elements.
+    pub fn max_len(mut self, max: usize) -> Self {
+        let len = self.vec.len() - self.free_list.len();
+        assert!(
+            len <= max,
+            "the stable vector already contains {len} elements, which exceeds the maximum length {max}"
+        );
+        self.max_len = max;
+        self
+    }
+
+    /// Let the underlying vector of this stable vector grow according to the given policy.
+    pub fn growth_policy(mut self, growth_policy: GrowthPolicy) -> Self {
+        self.growth_policy = growth_policy;
+        self
+    }
+
+    /// Let this stable vector release unused memory after removals according to the given policy.
+    pub fn shrink_policy(mut self, shrink_policy: ShrinkPolicy) -> Self {
+        self.shrink_policy = shrink_policy;
+        self
+    }
+
+    /// Let the insertions into this stable vector always reuse the smallest free index first.
+    ///
+    /// By default, the most recently freed index is reused first, so the indices assigned by insertions depend on the order of removals.
+    /// With a sorted free list, they only depend on which indices are free, at the cost of O(|holes|) removals.
+    /// The existing free list is sorted in O(|holes| log |holes|).
+    pub fn sorted_free_list(mut self) -> Self {
+        if !self.sorted_free_list {
+            self.free_list.sort_unstable_by(|a, b| b.cmp(a));
+            self.sorted_free_list = true;
+        }
+        self
+    }
+
+    /// Return `true` if this stable vector keeps its free list sorted, such that insertions always reuse the smallest free index first.
+    ///
+    /// See [`sorted_free_list`](OptionStableVec::sorted_free_list).
+    pub fn free_list_is_sorted(&self) -> bool {
+        self.sorted_free_list
+    }
+
+    /// Reserve the minimum capacity for exactly `additional` more slots in the underlying vector, without the over-allocation of amortised growth.
+    /// Insertions reuse holes first, so inserting `additional` elements afterwards does not reallocate.
+    ///
+    /// See [`Vec::reserve_exact`] for details.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.vec.reserve_exact(additional);
+    }
+
+    /// Reserve the minimum capacity for exactly `additional` more holes in the free list, without the over-allocation of amortised growth.
+    ///
+    /// See [`Vec::reserve_exact`] for details.
+    pub fn reserve_exact_free_list(&mut self, additional: usize) {
+        self.free_list.reserve_exact(additional);
+    }
+
+    /// Return the ratio of the number of elements to the capacity of the underlying vector,
+    /// or `1.0` if the underlying vector has not allocated any memory.
+    pub fn load_factor(&self) -> f64 {
+        let capacity = self.vec.capacity();
+        if capacity == 0 {
+            1.0
+        } else {
+            (self.vec.len() - self.free_list.len()) as f64 / capacity as f64
+        }
+    }
+
+    /// Return a summary of the internal state of this stable vector.
+    pub fn stats(&self) -> StableVecStats {
+        StableVecStats {
+            len: self.vec.len() - self.free_list.len(),
+            hole_count: self.free_list.len(),
+            index_bound: self.vec.len(),
+            vec_capacity: self.vec.capacity(),
+            free_list_capacity: self.free_list.capacity(),
+        }
+    }
+
+    /// Return an iterator over all slots of the underlying vector in ascending order of their raw index,
+    /// yielding `Some(element)` for slots that are mapped to an element and `None` for holes.
+    ///
+    /// Unlike [`iter`](StableVec::iter), this does not skip holes, which allows to visualise the fragmentation of the stable vector.
+    pub fn iter_slots(&self) -> impl '_ + Iterator<Item = (usize, Option<&Data>)> {
+        self.vec.iter().map(Option::as_ref).enumerate()
+    }
+
+    /// Return the underlying vector as a slice if it contains no holes, such that every slot is `Some` and slot `i` holds the element mapped to index `i`.
+    /// Return `None` if there is any hole.
+    ///
+    /// The slots cannot be borrowed as a `&[Data]`, because an `Option<Data>` does not in general have the same layout as `Data`,
+    /// so the elements would have to be copied out, e.g. with [`Iterator::flatten`].
+    pub fn as_dense_slots(&self) -> Option<&[Option<Data>]> {
+        self.free_list.is_empty().then_some(self.vec.as_slice())
+    }
+
+    /// Return a reference to the element at the given raw index, without converting it from or to the `Index` type.
+    /// This behaves like [`get`](StableVecReadAccess::get), returning an [`Error::UnmappedIndex`] if the raw index is not mapped to any element.
+    pub fn get_raw(&self, raw_index: usize) -> crate::error::Result<&Data> {
+        match self.vec.get(raw_index) {
+            Some(Some(element)) => Ok(element),
+            _ => Err(Error::UnmappedIndex { index: raw_index }),
+        }
+    }
+
+    /// Return a mutable reference to the element at the given raw index, without converting it from or to the `Index` type.
+    /// This behaves like [`get_mut`](StableVecAccess::get_mut), returning an [`Error::UnmappedIndex`] if the raw index is not mapped to any element.
+    pub fn get_raw_mut(&mut self, raw_index: usize) -> crate::error::Result<&mut Data> {
+        match self.vec.get_mut(raw_index) {
+            Some(Some(element)) => Ok(element),
+            _ => Err(Error::UnmappedIndex { index: raw_index }),
+        }
+    }
+
+    /// Return the number of holes in the underlying vector, i.e. the number of indices below the largest used index that are not mapped to any element.
+    pub fn free_index_count(&self) -> usize {
+        self.free_list.len()
+    }
+
+    /// Return the smallest raw index at which `length` consecutive slots are free, i.e. holes or beyond the end of the underlying vector.
+    /// Return `None` if such a run would exceed the maximum supported index.
+    ///
+    /// This scans the whole underlying vector, so it takes O(|underlying vector|) time.
+    pub fn find_free_run(&self, length: usize) -> Option<usize> {
+        if length == 0 {
+            return Some(0);
+        }
+
+        let mut start = 0;
+        for (index, slot) in self.vec.iter().enumerate() {
+            if slot.is_some() {
+                start = index + 1;
+            } else if index + 1 - start == length {
+                return Some(start);
+            }
+        }
+        (start.checked_add(length)? <= Self::max_index() + 1).then_some(start)
+    }
+
+    /// Convert this stable vector into a vector of its elements ordered by index, without any holes.
+    /// The indices of the elements are not preserved.
+    pub fn drain_into_dense(self) -> Vec<Data> {
+        self.vec.into_iter().flatten().collect()
+    }
+
+    /// Delete all elements from the stable vector, retaining the capacity of the underlying vector and free list.
+    /// This allows to refill the stable vector without reallocating.
+    pub fn clear_retaining_capacity(&mut self) {
+        self.vec.clear();
+        self.free_list.clear();
+    }
+
+    /// Convert this stable vector into one with a different index type, keeping all elements at their indices.
+    /// This only changes the type and does not touch the elements.
+    ///
+    /// The configuration of this stable vector is kept.
+    pub fn cast_index<NewIndex: StableVecIndex>(self) -> OptionStableVec<Data, NewIndex> {
+        if let Some(last_index) = self.vec.len().checked_sub(1) {
+            debug_assert_lossless_index::<NewIndex>(last_index);
+        }
+        OptionStableVec {
+            vec: self.vec,
+            free_list: self.free_list,
+            max_len: self.max_len,
+            growth_policy: self.growth_policy,
+            shrink_policy: self.shrink_policy,
+            sorted_free_list: self.sorted_free_list,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Convert each element with the fallible function `f`, keeping all elements at their indices and all holes as they are.
+    /// Return the first error returned by `f`, in which case `f` is not called for the remaining elements.
+    ///
+    /// The configuration of this stable vector is kept.
+    pub fn try_map<U, E>(
+        self,
+        mut f: impl FnMut(Data) -> Result<U, E>,
+    ) -> Result<OptionStableVec<U, Index>, E> {
+        let vec = self
+            .vec
+            .into_iter()
+            .map(|slot| slot.map(&mut f).transpose())
+            .collect::<Result<_, _>>()?;
+        Ok(OptionStableVec {
+            vec,
+            free_list: self.free_list,
+            max_len: self.max_len,
+            growth_policy: self.growth_policy,
+            shrink_policy: self.shrink_policy,
+            sorted_free_list: self.sorted_free_list,
+            phantom_data: PhantomData,
+        })
+    }
+
+    /// Check the internal invariants of this stable vector, returning an [`Error::InvariantViolation`] describing the first violation found.
+    ///
+    /// This verifies that every free list entry points to a hole in the underlying vector,
+    /// that there are no duplicate free list entries, and that every hole is tracked by the free list.
+    pub fn check_invariants(&self) -> crate::error::Result<()> {
+        let violation = |description: String| Err(Error::InvariantViolation { description });
+
+        let mut is_free = vec![false; self.vec.len()];
+        for &index in &self.free_list {
+            if index >= self.vec.len() {
+                return violation(format!(
+                    "free list entry {index} is out of bounds of the underlying vector of length {}",
+                    self.vec.len()
+                ));
+            }
+            if self.vec[index].is_some() {
+                return violation(format!("free list entry {index} is mapped to an element"));
+            }
+            if is_free[index] {
+                return violation(format!("free list entry {index} is duplicated"));
+            }
+            is_free[index] = true;
+        }
+
+        if self.sorted_free_list {
+            if let Some(window) = self
+                .free_list
+                .windows(2)
+                .find(|window| window[0] < window[1])
+            {
+                return violation(format!(
+                    "free list entries {} and {} are not sorted in descending order",
+                    window[0], window[1]
+                ));
+            }
+        }
+
+        if let Some(index) = self
+            .vec
+            .iter()
+            .zip(&is_free)
+            .position(|(element, &is_free)| element.is_none() && !is_free)
+        {
+            return violation(format!("hole {index} is not in the free list"));
+        }
+
+        let len = self.vec.len() - self.free_list.len();
+        if len > self.max_len {
+            return violation(format!(
+                "length {len} exceeds the maximum length {}",
+                self.max_len
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Return an [`Error::CapacityExceeded`] if inserting `additional` elements would exceed the maximum number of elements.
+    fn check_capacity(&self, additional: usize) -> crate::error::Result<()> {
+        let len = self.vec.len() - self.free_list.len();
+        if additional > self.max_len.saturating_sub(len) {
+            Err(Error::CapacityExceeded { max: self.max_len })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Return the largest index that the underlying vector can hold, as limited by the maximum size of an allocation.
+    fn max_index() -> usize {
+        (isize::MAX as usize / mem::size_of::<Option<Data>>().max(1)) - 1
+    }
+
+    /// Reserve capacity according to the growth policy, such that the underlying vector can hold `len` slots.
+    fn grow_to(&mut self, len: usize) {
+        if len <= self.vec.capacity() {
+            return;
+        }
+
+        if let GrowthPolicy::Linear { chunk_size } = self.growth_policy {
+            let capacity = len.max(self.vec.capacity().saturating_add(chunk_size));
+            self.vec.reserve_exact(capacity - self.vec.len());
+        }
+    }
+
+    /// Release unused memory if the shrink policy demands it.
+    fn apply_shrink_policy(&mut self) {
+        if let ShrinkPolicy::BelowLoadFactor { threshold } = self.shrink_policy {
+            if self.load_factor() < threshold {
+                self.trim_trailing_holes();
+                self.vec.shrink_to_fit();
+            }
+        }
+    }
+
+    /// Return an iterator over the pairs of (raw index, element) in this stable vec.
+    fn occupied_slots(&self) -> impl '_ + Iterator<Item = (usize, &Data)> {
+        self.vec
+            .iter()
+            .enumerate()
+            .filter_map(|(index, element)| element.as_ref().map(|element| (index, element)))
+    }
+
+    /// Create a stable vector from the given slots, where each `None` is a hole.
+    /// The element in slot `i` is mapped to index `i`, and the free list is rebuilt from the holes.
+    ///
+    /// Together with [`into_slots`](OptionStableVec::into_slots), this allows to round-trip a stable vector including its holes,
+    /// which [`From<Vec<Data>>`](OptionStableVec::from) cannot do.
+    pub fn from_slots(slots: Vec<Option<Data>>) -> Self {
+        let mut result = Self {
+            vec: slots,
+            ..Self::new()
+        };
+        result.rebuild_free_list();
+        result
+    }
+
+    /// Create an empty stable vector with the same slot layout as this one, where every slot of the underlying vector is a hole.
+    /// The result has the same underlying vector length and capacity, and the same configuration,
+    /// such that it can be filled at the same indices without reallocating.
+    pub fn clone_empty_like(&self) -> Self {
+        let mut vec = Vec::with_capacity(self.vec.capacity());
+        vec.resize_with(self.vec.len(), || None);
+        let mut result = Self {
+            vec,
+            max_len: self.max_len,
+            growth_policy: self.growth_policy,
+            shrink_policy: self.shrink_policy,
+            sorted_free_list: self.sorted_free_list,
+            ..Self::new()
+        };
+        result.rebuild_free_list();
+        result
+    }
+
+    /// Convert this stable vector into its underlying slots, where each `None` is a hole.
+    /// The element mapped to index `i` is in slot `i`.
+    pub fn into_slots(self) -> Vec<Option<Data>> {
+        self.vec
+    }
+
+    /// Convert this stable vector into its underlying vector and free list.
+    /// The element mapped to index `i` is in slot `i` of the underlying vector, and the free list contains the indices of all holes,
+    /// where the last one is reused first by insertions.
+    pub fn into_parts(self) -> (Vec<Option<Data>>, Vec<usize>) {
+        (self.vec, self.free_list)
+    }
+
+    /// Create a stable vector from the given underlying vector and free list, as returned by [`into_parts`](OptionStableVec::into_parts).
+    ///
+    /// If the free list does not contain exactly the indices of the holes in the underlying vector,
+    /// an [`Error::InvariantViolation`] is returned, as by [`check_invariants`](OptionStableVec::check_invariants).
+    pub fn try_from_parts(
+        vec: Vec<Option<Data>>,
+        free_list: Vec<usize>,
+    ) -> crate::error::Result<Self> {
+        let result = Self {
+            vec,
+            free_list,
+            ..Self::new()
+        };
+        result.check_invariants()?;
+        Ok(result)
+    }
+
+    /// Convert this stable vector into an array of its elements ordered by index, without any holes.
+    /// If the stable vector does not contain exactly `N` elements, it is returned unchanged as the error.
+    pub fn try_into_array<const N: usize>(self) -> Result<[Data; N], Self> {
+        if self.vec.len() - self.free_list.len() != N {
+            return Err(self);
+        }
+
+        let mut elements = self.vec.into_iter().flatten();
+        Ok(array::from_fn(|_| elements.next().unwrap()))
+    }
+
+    /// Remove the holes at the end of the underlying vector, and prune their indices from the free list.
+    /// Afterwards, the underlying vector ends with its highest occupied slot, or is empty if there are no elements.
+    ///
+    /// Unlike [`shrink_to_fit`](Vec::shrink_to_fit), this keeps the capacity of the underlying vector, such that it can grow again without reallocating.
+    pub fn trim_trailing_holes(&mut self) {
+        let len = self
+            .vec
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |index| index + 1);
+        if len < self.vec.len() {
+            self.vec.truncate(len);
+            self.free_list.retain(|&free_index| free_index < len);
+        }
+    }
+
+    /// Replace the free list with the indices of all holes in the underlying vector.
+    fn rebuild_free_list(&mut self) {
+        self.free_list.clear();
+        self.free_list.extend(
+            self.vec
+                .iter()
+                .enumerate()
+                .filter(|(_, element)| element.is_none())
+                .map(|(index, _)| index),
+        );
+        if self.sorted_free_list {
+            self.free_list.reverse();
+        }
+    }
+
+    /// Add the holes in the given range to the free list, which must all be greater than the indices already in the free list.
+    fn extend_free_list(&mut self, range: Range<usize>) {
+        if self.sorted_free_list {
+            self.free_list.splice(0..0, range.rev());
+        } else {
+            self.free_list.extend(range);
+        }
+    }
 }
 
-impl<Data, Index: StableVecIndex> StableVec<Data, Index> for OptionStableVec<Data, Index> {
-    fn insert(&mut self, element: Data) -> Index {
+impl<Data, Index: StableVecIndex> OptionStableVec<Data, Index> {
+    /// Insert a single element into the stable vector at an arbitrary index.
+    /// Return the index, or an [`Error::CapacityExceeded`] if the stable vector already contains its maximum number of elements.
+    pub fn try_insert(&mut self, element: Data) -> crate::error::Result<Index> {
+        self.check_capacity(1)?;
         let index = if let Some(index) = self.free_list.pop() {
             self.vec[index] = Some(element);
             index
         } else {
             let index = self.vec.len();
+            self.grow_to(index + 1);
             self.vec.push(Some(element));
             index
         };
-        index.into()
+        debug_assert_lossless_index::<Index>(index);
+        Ok(index.into())
     }
 
-    fn insert_in_place(&mut self, constructor: impl FnOnce(Index) -> Data) -> Index {
+    /// Insert a single element into the stable vector by constructing it in place.
+    /// Return the index, or an [`Error::CapacityExceeded`] if the stable vector already contains its maximum number of elements.
+    /// In the latter case, the constructor is not called.
+    pub fn try_insert_in_place(
+        &mut self,
+        constructor: impl FnOnce(Index) -> Data,
+    ) -> crate::error::Result<Index> {
+        self.check_capacity(1)?;
         let index = self.free_list.pop().unwrap_or(self.vec.len());
+        debug_assert_lossless_index::<Index>(index);
         let element = constructor(index.into());
 
         if index < self.vec.len() {
             self.vec[index] = Some(element);
         } else {
+            self.grow_to(index + 1);
             self.vec.push(Some(element));
         }
-        index.into()
+        Ok(index.into())
+    }
+
+    /// Insert the given elements, and return the range spanned by their indices.
+    ///
+    /// If there are no holes, the elements are appended to the underlying vector in one go,
+    /// and the range contains exactly their indices in order.
+    /// Otherwise, the elements are inserted one by one like with [`insert`](StableVec::insert),
+    /// and the range spans from their smallest to their largest index, so it may also contain indices of other elements.
+    ///
+    /// Panics if the stable vector would exceed its maximum number of elements.
+    pub fn insert_contiguous(
+        &mut self,
+        elements: impl ExactSizeIterator<Item = Data>,
+    ) -> Range<usize> {
+        if self.free_list.is_empty() {
+            self.check_capacity(elements.len()).unwrap();
+            let start = self.vec.len();
+            self.grow_to(start.saturating_add(elements.len()));
+            self.vec.extend(elements.map(Some));
+            if let Some(last_index) = self.vec.len().checked_sub(1) {
+                debug_assert_lossless_index::<Index>(last_index);
+            }
+            start..self.vec.len()
+        } else {
+            let mut range = self.vec.len()..self.vec.len();
+            for (offset, element) in elements.enumerate() {
+                let index = self.insert(element).into();
+                if offset == 0 {
+                    range = index..index + 1;
+                } else {
+                    range = range.start.min(index)..range.end.max(index + 1);
+                }
+            }
+            range
+        }
+    }
+
+    /// Insert the given elements at consecutive indices, and return the range of their indices.
+    /// The elements are placed into the first run of holes that is large enough, as found by [`find_free_run`](OptionStableVec::find_free_run),
+    /// or at the end of the underlying vector if there is no such run.
+    /// Unlike with [`insert_contiguous`](OptionStableVec::insert_contiguous), the range always contains exactly the indices of the given elements.
+    ///
+    /// Panics if the stable vector would exceed its maximum number of elements, or if the range would exceed the maximum supported index.
+    pub fn insert_block(&mut self, elements: Vec<Data>) -> Range<usize> {
+        self.check_capacity(elements.len()).unwrap();
+        let start = self
+            .find_free_run(elements.len())
+            .expect("the block exceeds the maximum supported index");
+        let end = start + elements.len();
+        if let Some(last_index) = end.checked_sub(1) {
+            debug_assert_lossless_index::<Index>(last_index);
+        }
+
+        if self.vec.len() < end {
+            self.grow_to(end);
+            self.vec.resize_with(end, || None);
+        }
+        self.free_list
+            .retain(|free_index| !(start..end).contains(free_index));
+        for (slot, element) in self.vec[start..end].iter_mut().zip(elements) {
+            *slot = Some(element);
+        }
+        start..end
+    }
+
+    /// Insert an element at every hole of the underlying vector, such that the stable vector contains no holes afterwards.
+    /// The elements are constructed by calling `f` with each filled index in ascending order, and no existing element is moved.
+    ///
+    /// Panics if the stable vector would exceed its maximum number of elements.
+    /// If `f` panics, the holes that were filled so far stay filled, and the stable vector is left in a consistent state.
+    pub fn fill_holes_with(&mut self, mut f: impl FnMut(Index) -> Data) {
+        self.check_capacity(self.free_list.len()).unwrap();
+        // Each hole stays in the free list until it is filled, such that a panic in `f` leaves the stable vector consistent.
+        self.free_list.sort_unstable_by(|a, b| b.cmp(a));
+        while let Some(&index) = self.free_list.last() {
+            self.vec[index] = Some(f(index.into()));
+            self.free_list.pop();
+        }
+    }
+
+    /// Return an iterator over the indices of the holes in the underlying vector, in the order in which they are reused by insertions.
+    ///
+    /// Unlike [`available_insertion_index_iterator`](StableVec::available_insertion_index_iterator),
+    /// this does not yield the indices after the end of the underlying vector.
+    pub fn free_indices(&self) -> impl '_ + Iterator<Item = Index> {
+        self.free_list.iter().rev().map(|&index| index.into())
+    }
+
+    /// Swap the elements at the given indices, such that the element previously at `a` is now at `b` and vice versa.
+    /// If either index is not mapped to an element, an [`Error::UnmappedIndex`] is returned and the stable vector is left unchanged.
+    pub fn swap_index_assignments(&mut self, a: Index, b: Index) -> crate::error::Result<()> {
+        let (a, b) = (a.into(), b.into());
+        for index in [a, b] {
+            if !self.vec.get(index).is_some_and(Option::is_some) {
+                return Err(Error::UnmappedIndex { index });
+            }
+        }
+
+        self.vec.swap(a, b);
+        Ok(())
+    }
+
+    /// Sort the elements with the given comparison function, and move them to the front of the underlying vector in sorted order.
+    /// The sort is stable, i.e. equal elements keep their relative order.
+    ///
+    /// Afterwards, the stable vector contains no holes.
+    /// Return the pairs of (old index, new index) for all elements, ordered by new index.
+    pub fn sort_by(
+        &mut self,
+        mut compare: impl FnMut(&Data, &Data) -> Ordering,
+    ) -> Vec<(Index, Index)> {
+        self.compact_sorted(|elements| elements.sort_by(|(_, a), (_, b)| compare(a, b)))
+    }
+
+    /// Move the elements to the front of the underlying vector, ordered by the given key.
+    /// The order is stable, i.e. elements with equal keys keep their relative order, and the key is computed only once per element.
+    ///
+    /// Afterwards, the stable vector contains no holes.
+    /// Return the pairs of (old index, new index) for all elements, ordered by new index.
+    pub fn compact_by_key<K: Ord>(
+        &mut self,
+        mut key: impl FnMut(&Data) -> K,
+    ) -> Vec<(Index, Index)> {
+        self.compact_sorted(|elements| elements.sort_by_cached_key(|(_, element)| key(element)))
+    }
+
+    /// Move the elements to the front of the underlying vector, keeping their order, and remove all holes.
+    /// This changes the indices of the elements, but unlike [`sort_by`](OptionStableVec::sort_by), it neither allocates nor returns the new indices.
+    /// Use it when no indices are held outside of the stable vector.
+    pub fn compact_in_place(&mut self) {
+        self.vec.retain(Option::is_some);
+        self.free_list.clear();
+    }
+
+    /// Move the elements to the front of the underlying vector in the order established by `sort`, which receives the pairs of (old index, element) ordered by old index.
+    /// Return the pairs of (old index, new index) for all elements, ordered by new index.
+    fn compact_sorted(
+        &mut self,
+        sort: impl FnOnce(&mut Vec<(usize, Data)>),
+    ) -> Vec<(Index, Index)> {
+        self.free_list.clear();
+        let mut elements: Vec<(usize, Data)> = self
+            .vec
+            .drain(..)
+            .enumerate()
+            .filter_map(|(index, element)| element.map(|element| (index, element)))
+            .collect();
+        sort(&mut elements);
+
+        let mut remapping = Vec::with_capacity(elements.len());
+        self.vec.extend(
+            elements
+                .into_iter()
+                .enumerate()
+                .map(|(new_index, (index, element))| {
+                    remapping.push((index.into(), new_index.into()));
+                    Some(element)
+                }),
+        );
+        remapping
+    }
+
+    /// Binary search the elements with the given comparison function, which returns the ordering of an element relative to the target.
+    /// If a matching element is found, its index is returned as `Ok`.
+    /// Otherwise, the position where a matching element could be inserted while maintaining the sorted order is returned as `Err`.
+    ///
+    /// **Precondition:** the stable vector must not contain holes, and its elements must be sorted by index with respect to the comparison function,
+    /// as is the case after [`sort_by`](OptionStableVec::sort_by).
+    /// If this does not hold, the result is unspecified, and debug builds panic if there are holes.
+    pub fn binary_search_by(
+        &self,
+        mut f: impl FnMut(&Data) -> Ordering,
+    ) -> std::result::Result<Index, usize> {
+        debug_assert!(
+            self.free_list.is_empty(),
+            "binary search requires a stable vector without holes"
+        );
+        self.vec
+            .binary_search_by(|element| element.as_ref().map_or(Ordering::Less, &mut f))
+            .map(Into::into)
+    }
+
+    /// Merge this and the other stable vector, whose elements must each be sorted by index with respect to the comparison function.
+    /// The result contains all elements in sorted order without holes, where equal elements from `self` come before those from `other`.
+    ///
+    /// See [`merge_sorted_with_remapping`](OptionStableVec::merge_sorted_with_remapping) to also obtain the new indices of the elements,
+    /// and for the configuration of the result.
+    pub fn merge_sorted(self, other: Self, compare: impl FnMut(&Data, &Data) -> Ordering) -> Self {
+        self.merge_sorted_with_remapping(other, compare).0
+    }
+
+    /// Merge this and the other stable vector, whose elements must each be sorted by index with respect to the comparison function.
+    /// The result contains all elements in sorted order without holes, where equal elements from `self` come before those from `other`.
+    ///
+    /// Additionally, return the pairs of (old index, new index) for the elements of `self` and `other` respectively, ordered by new index.
+    ///
+    /// The result keeps the configuration of `self`, like its maximum number of elements and its growth policy.
+    /// Panics if the result would exceed the maximum number of elements of `self`.
+    #[allow(clippy::type_complexity)]
+    pub fn merge_sorted_with_remapping(
+        self,
+        other: Self,
+        mut compare: impl FnMut(&Data, &Data) -> Ordering,
+    ) -> (Self, Vec<(Index, Index)>, Vec<(Index, Index)>) {
+        self.check_capacity(other.len()).unwrap();
+        let mut merged = Self {
+            vec: Vec::with_capacity(self.len() + other.len()),
+            max_len: self.max_len,
+            growth_policy: self.growth_policy,
+            shrink_policy: self.shrink_policy,
+            sorted_free_list: self.sorted_free_list,
+            ..Self::new()
+        };
+        let mut self_remapping = Vec::with_capacity(self.len());
+        let mut other_remapping = Vec::with_capacity(other.len());
+        let mut self_elements = self
+            .vec
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, element)| element.map(|element| (index, element)))
+            .peekable();
+        let mut other_elements = other
+            .vec
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, element)| element.map(|element| (index, element)))
+            .peekable();
+
+        loop {
+            let from_self = match (self_elements.peek(), other_elements.peek()) {
+                (Some((_, a)), Some((_, b))) => compare(a, b) != Ordering::Greater,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let ((index, element), remapping) = if from_self {
+                (self_elements.next().unwrap(), &mut self_remapping)
+            } else {
+                (other_elements.next().unwrap(), &mut other_remapping)
+            };
+            remapping.push((index.into(), merged.vec.len().into()));
+            merged.vec.push(Some(element));
+        }
+
+        (merged, self_remapping, other_remapping)
+    }
+
+    /// Move all elements of the other stable vector into this one, keeping their indices where possible.
+    /// Each element whose index is not mapped to an element in this stable vector is inserted at that index,
+    /// and all other elements are inserted at arbitrary indices afterwards.
+    ///
+    /// Return the pairs of (old index, new index) for the elements that were moved to a different index, ordered by old index.
+    ///
+    /// Panics if this stable vector would exceed its maximum number of elements.
+    pub fn merge_from(&mut self, other: Self) -> Vec<(Index, Index)> {
+        let mut conflicting = Vec::new();
+        for (index, element) in other.vec.into_iter().enumerate() {
+            let Some(element) = element else { continue };
+            if self.is_occupied(index.into()) {
+                conflicting.push((index, element));
+            } else {
+                self.insert_at_arbitrary_index(index.into(), element)
+                    .unwrap();
+            }
+        }
+
+        conflicting
+            .into_iter()
+            .map(|(index, element)| (index.into(), self.insert(element)))
+            .collect()
+    }
+
+    /// Remove and return the element at the given index, like [`remove`](StableVec::remove).
+    /// If this leaves holes at the end of the underlying vector, they are removed as well, such that the underlying vector does not keep growing in stack-like usage.
+    /// Removing any other element leaves a hole as usual.
+    pub fn remove_and_trim(&mut self, index: Index) -> crate::error::Result<Data> {
+        let element = self.remove(index)?;
+        self.trim_trailing_holes();
+        Ok(element)
+    }
+
+    /// Remove all elements `e` for which `f(&e)` returns `false`, evaluating `f` in parallel.
+    ///
+    /// The result is identical to [`retain`](StableVec::retain), including the state of the free list.
+    #[cfg(feature = "rayon")]
+    pub fn retain_par(&mut self, f: impl Fn(&Data) -> bool + Sync)
+    where
+        Data: Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        let removed: Vec<usize> = self
+            .vec
+            .par_iter()
+            .enumerate()
+            .filter_map(|(index, element)| match element {
+                Some(element) if !f(element) => Some(index),
+                _ => None,
+            })
+            .collect();
+        for index in removed {
+            self.vec[index] = None;
+            push_free_index(&mut self.free_list, self.sorted_free_list, index);
+        }
+        self.apply_shrink_policy();
+    }
+
+    /// Return the entry for the given index, which allows to inspect, insert or modify the element at that index in place.
+    pub fn entry(&mut self, index: Index) -> Entry<'_, Data, Index> {
+        let index = index.into();
+        debug_assert_lossless_index::<Index>(index);
+        Entry::new(self, index)
+    }
+
+    /// Create a read-only view of this stable vector that only grants access to the given indices.
+    /// All other indices behave as if they were not mapped to any element, even if they are.
+    pub fn subset_view<'a>(&'a self, indices: &'a [Index]) -> SubsetView<'a, Data, Index>
+    where
+        Index: Copy,
+    {
+        SubsetView::new(self, indices)
+    }
+
+    /// Remove all elements whose indices are in the given range, and return them together with their indices.
+    /// Bounds beyond the end of the underlying vector are clamped.
+    ///
+    /// **Warning**: the returned iterator must be completely exhausted in order to remove all elements in the range.
+    #[must_use = "this iterator must be completely exhausted in order to remove all elements in the range"]
+    pub fn remove_range(
+        &mut self,
+        range: impl RangeBounds<Index>,
+    ) -> impl '_ + Iterator<Item = (Index, Data)>
+    where
+        Index: Copy,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start.into(),
+            Bound::Excluded(&start) => Into::<usize>::into(start).saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => Into::<usize>::into(end).saturating_add(1),
+            Bound::Excluded(&end) => end.into(),
+            Bound::Unbounded => usize::MAX,
+        };
+        let end = end.min(self.vec.len());
+        let start = start.min(end);
+
+        let free_list = &mut self.free_list;
+        let sorted_free_list = self.sorted_free_list;
+        self.vec[start..end]
+            .iter_mut()
+            .enumerate()
+            .filter_map(move |(offset, slot)| {
+                let element = slot.take()?;
+                let index = start + offset;
+                push_free_index(free_list, sorted_free_list, index);
+                Some((index.into(), element))
+            })
+    }
+
+    /// Remove all elements whose indices are in the given range, and return them in ascending order of their indices.
+    /// Bounds beyond the end of the underlying vector are clamped.
+    ///
+    /// The slots of the range are then filled in ascending order with the elements of the largest indices beyond the range,
+    /// such that the underlying vector stays compact.
+    /// This breaks index stability for the moved elements, so the pairs of (old index, new index) of all moved elements are returned as well.
+    pub fn swap_remove_range(&mut self, range: Range<usize>) -> (Vec<Data>, Vec<(Index, Index)>) {
+        let end = range.end.min(self.vec.len());
+        let start = range.start.min(end);
+
+        let removed: Vec<Data> = self.vec[start..end]
+            .iter_mut()
+            .filter_map(Option::take)
+            .collect();
+
+        let mut remapping = Vec::new();
+        let mut source = self.vec.len();
+        for target in start..end {
+            while source > end && self.vec[source - 1].is_none() {
+                source -= 1;
+            }
+            if source <= end {
+                break;
+            }
+            source -= 1;
+            self.vec[target] = self.vec[source].take();
+            remapping.push((source.into(), target.into()));
+        }
+
+        self.rebuild_free_list();
+        self.trim_trailing_holes();
+        (removed, remapping)
+    }
+
+    /// Split this stable vector into the elements `e` for which `f(&e)` returns `true`, and those for which it returns `false`.
+    /// Both returned stable vectors keep all elements at their original indices, with holes where the elements went to the other one.
+    pub fn partition(self, mut f: impl FnMut(&Data) -> bool) -> (Self, Self) {
+        let mut matching = Vec::with_capacity(self.vec.len());
+        let mut non_matching = Vec::with_capacity(self.vec.len());
+        for element in self.vec {
+            match element {
+                Some(element) if f(&element) => {
+                    matching.push(Some(element));
+                    non_matching.push(None);
+                }
+                element => {
+                    matching.push(None);
+                    non_matching.push(element);
+                }
+            }
+        }
+
+        let with_config = |slots| {
+            let mut result = Self {
+                vec: slots,
+                max_len: self.max_len,
+                growth_policy: self.growth_policy,
+                shrink_policy: self.shrink_policy,
+                sorted_free_list: self.sorted_free_list,
+                ..Self::new()
+            };
+            result.rebuild_free_list();
+            result
+        };
+        (with_config(matching), with_config(non_matching))
+    }
+
+    /// Remove all elements, and return them together with their indices, as well as the indices of the holes, both ordered by index.
+    /// Afterwards, the stable vector is empty.
+    ///
+    /// Inserting the returned elements at their indices into an empty stable vector reconstructs the hole layout,
+    /// except for the order in which holes are reused.
+    pub fn take_all(&mut self) -> (Vec<(Index, Data)>, Vec<Index>) {
+        self.free_list.clear();
+        let mut elements = Vec::new();
+        let mut holes = Vec::new();
+        for (index, element) in self.vec.drain(..).enumerate() {
+            match element {
+                Some(element) => elements.push((index.into(), element)),
+                None => holes.push(index.into()),
+            }
+        }
+        (elements, holes)
+    }
+
+    /// Move each element from its index `i` to index `i + offset`, and rebuild the free list accordingly.
+    /// The indices below `offset` become holes.
+    ///
+    /// If the shifted indices would exceed the maximum supported index, or could not be represented by the index type,
+    /// an [`Error::IndexOutOfBounds`] is returned and the stable vector is left unchanged.
+    pub fn shift_indices(&mut self, offset: usize) -> crate::error::Result<()> {
+        let Some(last_index) = self.vec.len().checked_sub(1) else {
+            return Ok(());
+        };
+        Self::check_index(last_index.saturating_add(offset))?;
+
+        self.vec
+            .splice(0..0, iter::repeat_with(|| None).take(offset));
+        self.rebuild_free_list();
+        Ok(())
+    }
+
+    /// Move each element to the index returned by `f` for its current index, and rebuild the free list accordingly.
+    /// The function `f` is called exactly once for each index that is mapped to an element.
+    ///
+    /// If `f` maps two indices to the same index, an [`Error::OverlappingIndices`] is returned and the stable vector is left unchanged.
+    /// If `f` returns an index that exceeds the maximum supported index, an [`Error::IndexOutOfBounds`] is returned before anything is allocated,
+    /// and the stable vector is left unchanged as well.
+    pub fn renumber(&mut self, mut f: impl FnMut(Index) -> Index) -> crate::error::Result<()> {
+        let mapping: Vec<(usize, usize)> = self
+            .vec
+            .iter()
+            .enumerate()
+            .filter(|(_, element)| element.is_some())
+            .map(|(index, _)| (index, f(index.into()).into()))
+            .collect();
+
+        let mut new_indices: Vec<usize> = mapping.iter().map(|&(_, new_index)| new_index).collect();
+        new_indices.sort_unstable();
+        if let Some(window) = new_indices.windows(2).find(|window| window[0] == window[1]) {
+            return Err(Error::OverlappingIndices { index: window[0] });
+        }
+
+        let new_len = match new_indices.last() {
+            Some(&last_index) => {
+                Self::check_index(last_index)?;
+                last_index + 1
+            }
+            None => 0,
+        };
+        let mut vec = Vec::new();
+        vec.resize_with(new_len, || None);
+        for (index, new_index) in mapping {
+            vec[new_index] = self.vec[index].take();
+        }
+
+        self.vec = vec;
+        self.rebuild_free_list();
+        Ok(())
+    }
+
+    /// Return an [`Error::IndexOutOfBounds`] if the given index exceeds the maximum supported index,
+    /// or the largest index that the index type can represent.
+    fn check_index(index: usize) -> crate::error::Result<()> {
+        let max_index = Self::max_index().min(Index::MAX_INDEX);
+        if index > max_index {
+            Err(Error::IndexOutOfBounds { index, max_index })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<Data, Index: StableVecIndex> StableVec<Data, Index> for OptionStableVec<Data, Index> {
+    fn insert(&mut self, element: Data) -> Index {
+        self.try_insert(element).unwrap()
+    }
+
+    fn insert_ref(&mut self, element: Data) -> (Index, &mut Data) {
+        let index: usize = self.insert(element).into();
+        (index.into(), self.vec[index].as_mut().unwrap())
+    }
+
+    fn insert_in_place(&mut self, constructor: impl FnOnce(Index) -> Data) -> Index {
+        self.try_insert_in_place(constructor).unwrap()
     }
 
     fn insert_at(&mut self, index: Index, element: Data) -> crate::error::Result<()> {
         let expected_index = self.free_list.last().copied().unwrap_or(self.vec.len());
         let index = index.into();
         if expected_index == index {
-            let inserted_index = self.insert(element);
+            let inserted_index = self.try_insert(element)?;
             assert_eq!(inserted_index.into(), index);
             Ok(())
         } else {
@@ -79,15 +1178,33 @@ impl<Data, Index: StableVecIndex> StableVec<Data, Index> for OptionStableVec<Dat
         &mut self,
         index: Index,
         element: Data,
+    ) -> crate::error::Result<()> {
+        self.insert_in_place_at(index, |_| element)
+    }
+
+    fn insert_in_place_at(
+        &mut self,
+        index: Index,
+        constructor: impl FnOnce(Index) -> Data,
     ) -> crate::error::Result<()> {
         let index = index.into();
+        debug_assert_lossless_index::<Index>(index);
+        if self.vec.get(index).is_some_and(Option::is_some) {
+            return Err(Error::IndexAlreadyInUse { index });
+        }
+        let max_index = Self::max_index();
+        if index > max_index {
+            return Err(Error::IndexOutOfBounds { index, max_index });
+        }
+        self.check_capacity(1)?;
+        let element = constructor(index.into());
+
         if index >= self.vec.len() {
-            self.free_list.extend(self.vec.len()..index);
+            self.grow_to(index + 1);
+            self.extend_free_list(self.vec.len()..index);
             self.vec.resize_with(index + 1, || None);
             self.vec[index] = Some(element);
             Ok(())
-        } else if self.vec[index].is_some() {
-            Err(Error::IndexAlreadyInUse { index })
         } else {
             self.vec[index] = Some(element);
             self.free_list.retain(|&free_index| free_index != index);
@@ -95,26 +1212,59 @@ impl<Data, Index: StableVecIndex> StableVec<Data, Index> for OptionStableVec<Dat
         }
     }
 
+    fn reserve_index_range(&mut self, range: Range<usize>)
+    where
+        Data: Default,
+    {
+        if range.is_empty() {
+            return;
+        }
+
+        let occupied = self
+            .vec
+            .get(range.start..range.end.min(self.vec.len()))
+            .map_or(0, |slots| {
+                slots.iter().filter(|slot| slot.is_some()).count()
+            });
+        self.check_capacity(range.len() - occupied).unwrap();
+
+        if self.vec.len() < range.end {
+            self.grow_to(range.end);
+            self.extend_free_list(self.vec.len()..range.start);
+            self.vec.resize_with(range.end, || None);
+        }
+        self.free_list
+            .retain(|free_index| !range.contains(free_index));
+        for slot in &mut self.vec[range] {
+            if slot.is_none() {
+                *slot = Some(Default::default());
+            }
+        }
+    }
+
     fn remove(&mut self, index: Index) -> crate::error::Result<Data> {
         let index = index.into();
-        if index < self.vec.len() {
-            let element = Option::take(self.vec.get_mut(index).unwrap())
-                .ok_or(Error::UnmappedIndex { index })?;
-            self.free_list.push(index);
-            Ok(element)
-        } else {
-            Err(Error::UnmappedIndex { index })
-        }
+        self.take(index.into())
+            .ok_or(Error::UnmappedIndex { index })
+    }
+
+    fn take(&mut self, index: Index) -> Option<Data> {
+        let index = index.into();
+        let element = self.vec.get_mut(index)?.take()?;
+        push_free_index(&mut self.free_list, self.sorted_free_list, index);
+        self.apply_shrink_policy();
+        Some(element)
     }
 
-    fn available_insertion_index_iterator<'result>(&self) -> impl 'result + Iterator<Item = Index>
+    #[allow(refining_impl_trait)]
+    fn available_insertion_index_iterator<'result>(&self) -> AvailableInsertionIndexIterator<Index>
     where
         Index: 'result,
     {
         AvailableInsertionIndexIterator::new(self.free_list.clone(), self.vec.len())
     }
 
-    fn iter<'this>(&'this self) -> impl 'this + Iterator<Item = (Index, &'this Data)>
+    fn iter<'this>(&'this self) -> impl 'this + FusedIterator<Item = (Index, &'this Data)>
     where
         Data: 'this,
     {
@@ -124,7 +1274,9 @@ impl<Data, Index: StableVecIndex> StableVec<Data, Index> for OptionStableVec<Dat
             .filter_map(|(index, element)| element.as_ref().map(|element| (index.into(), element)))
     }
 
-    fn iter_mut<'this>(&'this mut self) -> impl 'this + Iterator<Item = (Index, &'this mut Data)>
+    fn iter_mut<'this>(
+        &'this mut self,
+    ) -> impl 'this + FusedIterator<Item = (Index, &'this mut Data)>
     where
         Data: 'this,
     {
@@ -134,37 +1286,53 @@ impl<Data, Index: StableVecIndex> StableVec<Data, Index> for OptionStableVec<Dat
             .filter_map(|(index, element)| element.as_mut().map(|element| (index.into(), element)))
     }
 
-    fn retain(&mut self, mut f: impl FnMut(&Data) -> bool) {
-        for index in 0..self.vec.len() {
-            if let Some(element) = self.vec[index].as_ref() {
-                if !f(element) {
-                    self.remove(index.into()).unwrap();
-                }
+    fn retain(&mut self, f: impl FnMut(&Data) -> bool) {
+        self.retain_counted(f);
+    }
+
+    fn retain_counted(&mut self, mut f: impl FnMut(&Data) -> bool) -> usize {
+        let len = self.len();
+        self.retain_mut_with_index(|_, element| f(element));
+        len - self.len()
+    }
+
+    fn retain_mut_with_index(&mut self, mut f: impl FnMut(Index, &mut Data) -> bool) {
+        for (index, slot) in self.vec.iter_mut().enumerate() {
+            if slot
+                .as_mut()
+                .is_some_and(|element| !f(index.into(), element))
+            {
+                *slot = None;
+                push_free_index(&mut self.free_list, self.sorted_free_list, index);
             }
         }
+        self.apply_shrink_policy();
+    }
+
+    fn compact_stable(&mut self) -> Vec<(Index, Index)> {
+        self.compact_sorted(|_| {})
     }
 
+    /// Delete all elements from the stable vector.
+    ///
+    /// This currently retains the capacity of the underlying vector and free list, but this may change in a future major version.
+    /// Use [`clear_retaining_capacity`](OptionStableVec::clear_retaining_capacity) to rely on the capacity being retained.
     fn clear(&mut self) {
-        self.vec.clear();
-        self.free_list.clear();
+        self.clear_retaining_capacity();
     }
 }
 
-impl<Data, Index: StableVecIndex> StableVecAccess<Data, Index> for OptionStableVec<Data, Index> {
+impl<Data, Index: StableVecIndex> StableVecReadAccess<Data, Index>
+    for OptionStableVec<Data, Index>
+{
     fn get(&self, index: Index) -> crate::error::Result<&Data> {
         let index = index.into();
-        match self.vec.get(index) {
-            Some(Some(element)) => Ok(element),
-            _ => Err(Error::UnmappedIndex { index }),
-        }
+        debug_assert_lossless_index::<Index>(index);
+        self.get_raw(index)
     }
 
-    fn get_mut(&mut self, index: Index) -> crate::error::Result<&mut Data> {
-        let index = index.into();
-        match self.vec.get_mut(index) {
-            Some(Some(element)) => Ok(element),
-            _ => Err(Error::UnmappedIndex { index }),
-        }
+    fn is_occupied(&self, index: Index) -> bool {
+        matches!(self.vec.get(index.into()), Some(Some(_)))
     }
 
     fn len(&self) -> usize {
@@ -172,6 +1340,21 @@ impl<Data, Index: StableVecIndex> StableVecAccess<Data, Index> for OptionStableV
     }
 }
 
+impl<Data, Index: StableVecIndex> StableVecAccess<Data, Index> for OptionStableVec<Data, Index> {
+    fn get_mut(&mut self, index: Index) -> crate::error::Result<&mut Data> {
+        let index = index.into();
+        debug_assert_lossless_index::<Index>(index);
+        self.get_raw_mut(index)
+    }
+
+    fn get_disjoint_mut_opt<const N: usize>(
+        &mut self,
+        indices: [Index; N],
+    ) -> crate::error::Result<[Option<&mut Data>; N]> {
+        get_disjoint_slots_mut(&mut self.vec, indices.map(Into::into))
+    }
+}
+
 impl<Data, Index> Default for OptionStableVec<Data, Index> {
     fn default() -> Self {
         Self::new()
@@ -183,6 +1366,10 @@ impl<Data: Clone, Index> Clone for OptionStableVec<Data, Index> {
         Self {
             vec: self.vec.clone(),
             free_list: self.free_list.clone(),
+            max_len: self.max_len,
+            growth_policy: self.growth_policy,
+            shrink_policy: self.shrink_policy,
+            sorted_free_list: self.sorted_free_list,
             phantom_data: self.phantom_data,
         }
     }
@@ -190,18 +1377,56 @@ impl<Data: Clone, Index> Clone for OptionStableVec<Data, Index> {
 
 impl<Data: Eq, Index> PartialEq for OptionStableVec<Data, Index> {
     fn eq(&self, other: &Self) -> bool {
-        self.vec == other.vec
+        self.occupied_slots().eq(other.occupied_slots())
     }
 }
 
 impl<Data: Eq, Index> Eq for OptionStableVec<Data, Index> {}
 
+impl<Data: Ord, Index> PartialOrd for OptionStableVec<Data, Index> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares the sequences of (index, element) pairs lexicographically, ignoring holes.
+impl<Data: Ord, Index> Ord for OptionStableVec<Data, Index> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.occupied_slots().cmp(other.occupied_slots())
+    }
+}
+
 impl<Data, Index> From<Vec<Data>> for OptionStableVec<Data, Index> {
     fn from(value: Vec<Data>) -> Self {
         value.into_iter().collect()
     }
 }
 
+/// Places each element at its key, with holes at all unused indices.
+/// If a key exceeds the maximum index that the underlying vector can hold, an [`Error::IndexOutOfBounds`] is returned.
+/// If the slots up to the largest key cannot be allocated, an [`Error::AllocationFailed`] is returned.
+impl<Data, Index, State> TryFrom<HashMap<usize, Data, State>> for OptionStableVec<Data, Index> {
+    type Error = Error;
+
+    fn try_from(value: HashMap<usize, Data, State>) -> Result<Self, Self::Error> {
+        let max_index = Self::max_index();
+        if let Some(&index) = value.keys().find(|&&index| index > max_index) {
+            return Err(Error::IndexOutOfBounds { index, max_index });
+        }
+
+        let len = value.keys().max().map_or(0, |&index| index + 1);
+        let mut slots = Vec::new();
+        slots
+            .try_reserve_exact(len)
+            .map_err(|_| Error::AllocationFailed { len })?;
+        slots.resize_with(len, || None);
+        for (index, element) in value {
+            slots[index] = Some(element);
+        }
+        Ok(Self::from_slots(slots))
+    }
+}
+
 impl<Data, Index> IntoIterator for OptionStableVec<Data, Index> {
     type Item = Data;
     type IntoIter = iter::Flatten<vec::IntoIter<Option<Data>>>;
@@ -215,13 +1440,12 @@ impl<Data, Index> FromIterator<Data> for OptionStableVec<Data, Index> {
     fn from_iter<T: IntoIterator<Item = Data>>(iter: T) -> Self {
         Self {
             vec: iter.into_iter().map(Some).collect(),
-            free_list: Default::default(),
-            phantom_data: Default::default(),
+            ..Self::new()
         }
     }
 }
 
-impl<Data: Debug, Index: StableVecIndex> Debug for OptionStableVec<Data, Index> {
+impl<Data: Debug, Index: StableVecIndex + Debug> Debug for OptionStableVec<Data, Index> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "OptionStableVec [")?;
 
@@ -233,9 +1457,58 @@ impl<Data: Debug, Index: StableVecIndex> Debug for OptionStableVec<Data, Index>
             } else {
                 once = true;
             }
-            write!(f, "({index}, {element:?})")?;
+            let index: Index = index.into();
+            write!(f, "({index:?}, {element:?})")?;
         }
 
         write!(f, "]")
     }
 }
+
+/// Add the given index to the free list.
+/// If the free list is sorted, it is kept in descending order, such that the smallest free index is reused first.
+fn push_free_index(free_list: &mut Vec<usize>, sorted: bool, index: usize) {
+    if sorted {
+        let position = free_list.partition_point(|&free_index| free_index > index);
+        free_list.insert(position, index);
+    } else {
+        free_list.push(index);
+    }
+}
+
+/// Get mutable references to the elements in the given slots, or `None` for slots that are holes or out of bounds.
+/// If the indices are not pairwise distinct, an [`Error::OverlappingIndices`] is returned.
+pub(crate) fn get_disjoint_slots_mut<Data, const N: usize>(
+    slots: &mut [Option<Data>],
+    indices: [usize; N],
+) -> crate::error::Result<[Option<&mut Data>; N]> {
+    let mut order: [usize; N] = array::from_fn(|position| position);
+    order.sort_unstable_by_key(|&position| indices[position]);
+    if let Some(window) = order
+        .windows(2)
+        .find(|window| indices[window[0]] == indices[window[1]])
+    {
+        return Err(Error::OverlappingIndices {
+            index: indices[window[0]],
+        });
+    }
+
+    let mut result: [Option<&mut Data>; N] = array::from_fn(|_| None);
+    let mut remaining = slots;
+    let mut offset = 0;
+    for position in order {
+        let index = indices[position];
+        if index >= offset + remaining.len() {
+            break;
+        }
+
+        let (slot, tail) = mem::take(&mut remaining)[index - offset..]
+            .split_first_mut()
+            .unwrap();
+        result[position] = slot.as_mut();
+        remaining = tail;
+        offset = index + 1;
+    }
+
+    Ok(result)
+}
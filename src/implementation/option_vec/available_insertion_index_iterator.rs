@@ -4,6 +4,9 @@ use std::marker::PhantomData;
 ///
 /// **WARNING:** This iterator is lifetime-independent of its underlying vector,
 /// but quietly becomes invalid if the underlying vec is changed.
+///
+/// This iterator is intentionally infinite, since there is always a next available insertion index after the end of the underlying vec.
+/// Hence it never returns `None`, and does not implement [`FusedIterator`](std::iter::FusedIterator).
 pub struct AvailableInsertionIndexIterator<Index> {
     free_list: Vec<usize>,
     next_index: usize,
@@ -20,6 +23,16 @@ impl<Index> AvailableInsertionIndexIterator<Index> {
     }
 }
 
+impl<Index> Clone for AvailableInsertionIndexIterator<Index> {
+    fn clone(&self) -> Self {
+        Self {
+            free_list: self.free_list.clone(),
+            next_index: self.next_index,
+            index: self.index,
+        }
+    }
+}
+
 impl<Index: From<usize>> Iterator for AvailableInsertionIndexIterator<Index> {
     type Item = Index;
 
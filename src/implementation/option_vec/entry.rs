@@ -0,0 +1,136 @@
+use crate::interface::{StableVec, StableVecIndex};
+
+use super::OptionStableVec;
+
+/// A view into a single index of an [`OptionStableVec`], which is either mapped to an element or not.
+///
+/// This is returned by [`OptionStableVec::entry`].
+pub enum Entry<'a, Data, Index> {
+    /// The index is mapped to an element.
+    Occupied(OccupiedEntry<'a, Data, Index>),
+    /// The index is not mapped to any element.
+    Vacant(VacantEntry<'a, Data, Index>),
+}
+
+/// A view into an index of an [`OptionStableVec`] that is mapped to an element.
+pub struct OccupiedEntry<'a, Data, Index> {
+    vec: &'a mut OptionStableVec<Data, Index>,
+    index: usize,
+}
+
+/// A view into an index of an [`OptionStableVec`] that is not mapped to any element.
+pub struct VacantEntry<'a, Data, Index> {
+    vec: &'a mut OptionStableVec<Data, Index>,
+    index: usize,
+}
+
+impl<'a, Data, Index: StableVecIndex> Entry<'a, Data, Index> {
+    pub(crate) fn new(vec: &'a mut OptionStableVec<Data, Index>, index: usize) -> Self {
+        if vec.vec.get(index).is_some_and(Option::is_some) {
+            Self::Occupied(OccupiedEntry { vec, index })
+        } else {
+            Self::Vacant(VacantEntry { vec, index })
+        }
+    }
+
+    /// Return the index of this entry.
+    pub fn index(&self) -> Index {
+        match self {
+            Self::Occupied(entry) => entry.index(),
+            Self::Vacant(entry) => entry.index(),
+        }
+    }
+
+    /// Return a mutable reference to the element of this entry, inserting the given element first if the entry is vacant.
+    pub fn or_insert(self, element: Data) -> &'a mut Data {
+        self.or_insert_with_index(|_| element)
+    }
+
+    /// Return a mutable reference to the element of this entry, inserting the result of `f` first if the entry is vacant.
+    pub fn or_insert_with(self, f: impl FnOnce() -> Data) -> &'a mut Data {
+        self.or_insert_with_index(|_| f())
+    }
+
+    /// Return a mutable reference to the element of this entry, inserting the result of `f` first if the entry is vacant.
+    /// The function `f` receives the index of this entry, such that the element can embed its own index,
+    /// like with [`insert_in_place`](StableVec::insert_in_place).
+    pub fn or_insert_with_index(self, f: impl FnOnce(Index) -> Data) -> &'a mut Data {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert_in_place(f),
+        }
+    }
+
+    /// Return a mutable reference to the element of this entry, inserting the default value first if the entry is vacant.
+    pub fn or_default(self) -> &'a mut Data
+    where
+        Data: Default,
+    {
+        self.or_insert_with(Default::default)
+    }
+
+    /// Call `f` with a mutable reference to the element of this entry if it is occupied, and return the entry.
+    pub fn and_modify(mut self, f: impl FnOnce(&mut Data)) -> Self {
+        if let Self::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, Data, Index: StableVecIndex> OccupiedEntry<'a, Data, Index> {
+    /// Return the index of this entry.
+    pub fn index(&self) -> Index {
+        self.index.into()
+    }
+
+    /// Return a reference to the element of this entry.
+    pub fn get(&self) -> &Data {
+        self.vec.vec[self.index].as_ref().unwrap()
+    }
+
+    /// Return a mutable reference to the element of this entry.
+    pub fn get_mut(&mut self) -> &mut Data {
+        self.vec.vec[self.index].as_mut().unwrap()
+    }
+
+    /// Convert this entry into a mutable reference to its element, with the lifetime of the stable vector.
+    pub fn into_mut(self) -> &'a mut Data {
+        self.vec.vec[self.index].as_mut().unwrap()
+    }
+
+    /// Replace the element of this entry with the given element, and return the old element.
+    pub fn insert(&mut self, element: Data) -> Data {
+        self.vec.vec[self.index].replace(element).unwrap()
+    }
+
+    /// Remove the element of this entry from the stable vector, and return it.
+    pub fn remove(self) -> Data {
+        self.vec.remove(self.index.into()).unwrap()
+    }
+}
+
+impl<'a, Data, Index: StableVecIndex> VacantEntry<'a, Data, Index> {
+    /// Return the index of this entry.
+    pub fn index(&self) -> Index {
+        self.index.into()
+    }
+
+    /// Insert the given element at the index of this entry, and return a mutable reference to it.
+    ///
+    /// Panics if the stable vector already contains its maximum number of elements, or if the index exceeds the maximum supported index.
+    pub fn insert(self, element: Data) -> &'a mut Data {
+        self.insert_in_place(|_| element)
+    }
+
+    /// Insert the element returned by `constructor` at the index of this entry, and return a mutable reference to it.
+    /// The constructor receives the index of this entry, such that the element can embed its own index.
+    ///
+    /// Panics if the stable vector already contains its maximum number of elements, or if the index exceeds the maximum supported index.
+    pub fn insert_in_place(self, constructor: impl FnOnce(Index) -> Data) -> &'a mut Data {
+        self.vec
+            .insert_in_place_at(self.index.into(), constructor)
+            .unwrap();
+        self.vec.vec[self.index].as_mut().unwrap()
+    }
+}
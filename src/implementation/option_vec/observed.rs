@@ -0,0 +1,153 @@
+use std::ops::RangeBounds;
+
+use crate::{
+    error::Result,
+    interface::{StableVec, StableVecAccess, StableVecIndex, StableVecReadAccess},
+};
+
+use super::OptionStableVec;
+
+/// An [`OptionStableVec`] that calls an observer function with the index and a reference to each element that is removed from it.
+///
+/// This allows to keep derived data structures, like a reverse map from elements to indices, consistent with the stable vector.
+/// All methods of this wrapper that remove elements while keeping the stable vector call the observer,
+/// and the wrapped stable vector is only accessible immutably, such that no removal is missed.
+/// The observer is not called when an element is replaced, or when the wrapper is consumed or dropped.
+///
+/// The observer is a type parameter rather than a boxed function, such that it may capture non-`Send` state like an `Rc<RefCell<_>>`,
+/// while stable vectors without an observer are unaffected.
+pub struct ObservedStableVec<Data, Index, Observer> {
+    vec: OptionStableVec<Data, Index>,
+    observer: Observer,
+}
+
+impl<Data, Index, Observer> ObservedStableVec<Data, Index, Observer> {
+    /// Wrap the given stable vector, such that `observer` is called for each element that is removed from it.
+    pub fn new(vec: OptionStableVec<Data, Index>, observer: Observer) -> Self {
+        Self { vec, observer }
+    }
+
+    /// Return a reference to the wrapped stable vector.
+    pub fn stable_vec(&self) -> &OptionStableVec<Data, Index> {
+        &self.vec
+    }
+
+    /// Unwrap the stable vector and the observer.
+    pub fn into_parts(self) -> (OptionStableVec<Data, Index>, Observer) {
+        (self.vec, self.observer)
+    }
+}
+
+impl<Data, Index: StableVecIndex, Observer: FnMut(Index, &Data)>
+    ObservedStableVec<Data, Index, Observer>
+{
+    /// Insert a single element into the stable vector at an arbitrary index, like with [`StableVec::insert`].
+    pub fn insert(&mut self, element: Data) -> Index {
+        self.vec.insert(element)
+    }
+
+    /// Insert a single element into the stable vector by constructing it in place, like with [`StableVec::insert_in_place`].
+    pub fn insert_in_place(&mut self, constructor: impl FnOnce(Index) -> Data) -> Index {
+        self.vec.insert_in_place(constructor)
+    }
+
+    /// Remove and return the element at the given index, like with [`StableVec::remove`], and call the observer for it.
+    pub fn remove(&mut self, index: Index) -> Result<Data> {
+        let index: usize = index.into();
+        let element = self.vec.remove(index.into())?;
+        (self.observer)(index.into(), &element);
+        Ok(element)
+    }
+
+    /// Remove and return the element at the given index, like with [`StableVec::take`], and call the observer for it.
+    pub fn take(&mut self, index: Index) -> Option<Data> {
+        self.remove(index).ok()
+    }
+
+    /// Remove all elements `e` for which `f(&e)` returns `false`, like with [`StableVec::retain`], and call the observer for each of them.
+    pub fn retain(&mut self, mut f: impl FnMut(&Data) -> bool) {
+        self.retain_mut_with_index(|_, element| f(element));
+    }
+
+    /// Remove all elements `e` with index `i` for which `f(i, &mut e)` returns `false`, like with [`StableVec::retain_mut_with_index`],
+    /// and call the observer for each of them.
+    pub fn retain_mut_with_index(&mut self, mut f: impl FnMut(Index, &mut Data) -> bool) {
+        let observer = &mut self.observer;
+        self.vec.retain_mut_with_index(|index, element| {
+            let index: usize = index.into();
+            let keep = f(index.into(), element);
+            if !keep {
+                observer(index.into(), element);
+            }
+            keep
+        });
+    }
+
+    /// Remove all elements whose indices are in the given range, like with [`OptionStableVec::remove_range`], and call the observer for each of them.
+    ///
+    /// **Warning**: the returned iterator must be completely exhausted in order to remove all elements in the range.
+    #[must_use = "this iterator must be completely exhausted in order to remove all elements in the range"]
+    pub fn remove_range(
+        &mut self,
+        range: impl RangeBounds<Index>,
+    ) -> impl '_ + Iterator<Item = (Index, Data)>
+    where
+        Index: Copy,
+    {
+        let observer = &mut self.observer;
+        self.vec.remove_range(range).map(|(index, element)| {
+            observer(index, &element);
+            (index, element)
+        })
+    }
+
+    /// Remove all elements, like with [`OptionStableVec::take_all`], and call the observer for each of them.
+    pub fn take_all(&mut self) -> (Vec<(Index, Data)>, Vec<Index>) {
+        let (elements, holes) = self.vec.take_all();
+        let elements = elements
+            .into_iter()
+            .map(|(index, element)| {
+                let index: usize = index.into();
+                (self.observer)(index.into(), &element);
+                (index.into(), element)
+            })
+            .collect();
+        (elements, holes)
+    }
+
+    /// Delete all elements from the stable vector, like with [`StableVec::clear`], and call the observer for each of them.
+    pub fn clear(&mut self) {
+        self.take_all();
+    }
+}
+
+impl<Data, Index: StableVecIndex, Observer> StableVecReadAccess<Data, Index>
+    for ObservedStableVec<Data, Index, Observer>
+{
+    fn get(&self, index: Index) -> Result<&Data> {
+        self.vec.get(index)
+    }
+
+    fn is_occupied(&self, index: Index) -> bool {
+        self.vec.is_occupied(index)
+    }
+
+    fn len(&self) -> usize {
+        self.vec.len()
+    }
+}
+
+impl<Data, Index: StableVecIndex, Observer> StableVecAccess<Data, Index>
+    for ObservedStableVec<Data, Index, Observer>
+{
+    fn get_mut(&mut self, index: Index) -> Result<&mut Data> {
+        self.vec.get_mut(index)
+    }
+
+    fn get_disjoint_mut_opt<const N: usize>(
+        &mut self,
+        indices: [Index; N],
+    ) -> Result<[Option<&mut Data>; N]> {
+        self.vec.get_disjoint_mut_opt(indices)
+    }
+}
@@ -0,0 +1,169 @@
+//! Serialization of [`OptionStableVec`] with [`serde`].
+//!
+//! The serialized format is independent of the internal layout of the stable vector.
+//! It is a struct with the following fields:
+//!
+//! * `version`: the version of the format, currently `1`.
+//! * `index_bound`: one more than the largest index that was ever used, such that trailing holes survive a round trip.
+//! * `elements`: the pairs of (index, element), ordered by index.
+//!
+//! The version is always the first field, and it is read before anything else, such that the layout of the remaining fields may depend on it.
+//! Data with an unsupported version is rejected without decoding the remaining fields.
+//!
+//! The free list is rebuilt from the holes on deserialization, so the order in which holes are reused may differ from the original stable vector.
+//! The maximum number of elements, the growth policy, the shrink policy and whether the free list is kept sorted are not serialized.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::OptionStableVec;
+
+/// The version of the serialized format.
+const FORMAT_VERSION: u32 = 1;
+
+/// The names of the fields of the serialized format.
+const FIELDS: &[&str] = &["version", "index_bound", "elements"];
+
+#[derive(Serialize)]
+#[serde(rename = "OptionStableVec")]
+struct SerializedStableVecRef<'a, Data> {
+    version: u32,
+    index_bound: usize,
+    elements: Vec<(usize, &'a Data)>,
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum Field {
+    Version,
+    IndexBound,
+    Elements,
+}
+
+struct StableVecVisitor<Data, Index> {
+    phantom_data: PhantomData<(Data, Index)>,
+}
+
+impl<Data: Serialize, Index> Serialize for OptionStableVec<Data, Index> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedStableVecRef {
+            version: FORMAT_VERSION,
+            index_bound: self.vec.len(),
+            elements: self.occupied_slots().collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, Data: Deserialize<'de>, Index> Deserialize<'de> for OptionStableVec<Data, Index> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct(
+            "OptionStableVec",
+            FIELDS,
+            StableVecVisitor {
+                phantom_data: PhantomData,
+            },
+        )
+    }
+}
+
+impl<'de, Data: Deserialize<'de>, Index> de::Visitor<'de> for StableVecVisitor<Data, Index> {
+    type Value = OptionStableVec<Data, Index>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "a stable vector in format version {FORMAT_VERSION}"
+        )
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let version = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        check_version(version)?;
+
+        let index_bound = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let elements = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        OptionStableVec::from_serialized(index_bound, elements)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        match map.next_key()? {
+            Some(Field::Version) => check_version(map.next_value()?)?,
+            Some(_) => return Err(de::Error::custom("expected the field `version` first")),
+            None => return Err(de::Error::missing_field("version")),
+        }
+
+        let mut index_bound = None;
+        let mut elements = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Version => return Err(de::Error::duplicate_field("version")),
+                Field::IndexBound if index_bound.is_some() => {
+                    return Err(de::Error::duplicate_field("index_bound"))
+                }
+                Field::IndexBound => index_bound = Some(map.next_value()?),
+                Field::Elements if elements.is_some() => {
+                    return Err(de::Error::duplicate_field("elements"))
+                }
+                Field::Elements => elements = Some(map.next_value()?),
+            }
+        }
+
+        let index_bound = index_bound.ok_or_else(|| de::Error::missing_field("index_bound"))?;
+        let elements = elements.ok_or_else(|| de::Error::missing_field("elements"))?;
+        OptionStableVec::from_serialized(index_bound, elements)
+    }
+}
+
+/// Return an error if the given format version is not supported.
+fn check_version<E: de::Error>(version: u32) -> Result<(), E> {
+    if version == FORMAT_VERSION {
+        Ok(())
+    } else {
+        Err(E::custom(format!(
+            "unsupported format version {version}, expected {FORMAT_VERSION}"
+        )))
+    }
+}
+
+impl<Data, Index> OptionStableVec<Data, Index> {
+    /// Create a stable vector from the fields of the serialized format, with holes at all indices below `index_bound` that have no element.
+    fn from_serialized<E: de::Error>(
+        index_bound: usize,
+        elements: Vec<(usize, Data)>,
+    ) -> Result<Self, E> {
+        if index_bound > Self::max_index().saturating_add(1) {
+            return Err(E::custom(format!(
+                "index bound {index_bound} exceeds the maximum supported index {}",
+                Self::max_index()
+            )));
+        }
+
+        let mut slots = Vec::new();
+        slots.try_reserve_exact(index_bound).map_err(|error| {
+            E::custom(format!(
+                "cannot allocate the index bound {index_bound}: {error}"
+            ))
+        })?;
+        slots.resize_with(index_bound, || None);
+        for (index, element) in elements {
+            let Some(slot) = slots.get_mut(index) else {
+                return Err(E::custom(format!(
+                    "index {index} is out of bounds of the index bound {index_bound}"
+                )));
+            };
+            if slot.replace(element).is_some() {
+                return Err(E::custom(format!("index {index} is duplicated")));
+            }
+        }
+
+        Ok(Self::from_slots(slots))
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{
+    error::{Error, Result},
+    interface::{StableVecIndex, StableVecReadAccess},
+};
+
+use super::OptionStableVec;
+
+/// A read-only view of an [`OptionStableVec`] that only grants access to a subset of its indices.
+///
+/// Indices that are not in the subset behave as if they were not mapped to any element.
+pub struct SubsetView<'a, Data, Index> {
+    vec: &'a OptionStableVec<Data, Index>,
+    indices: Vec<usize>,
+    len: usize,
+}
+
+impl<'a, Data, Index: StableVecIndex + Copy> SubsetView<'a, Data, Index> {
+    pub(crate) fn new(vec: &'a OptionStableVec<Data, Index>, indices: &[Index]) -> Self {
+        let mut indices: Vec<usize> = indices.iter().map(|&index| index.into()).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        let len = indices
+            .iter()
+            .filter(|&&index| vec.is_occupied(index.into()))
+            .count();
+
+        Self { vec, indices, len }
+    }
+
+    fn contains(&self, index: Index) -> bool {
+        self.indices.binary_search(&index.into()).is_ok()
+    }
+}
+
+impl<Data, Index: StableVecIndex + Copy> StableVecReadAccess<Data, Index>
+    for SubsetView<'_, Data, Index>
+{
+    fn get(&self, index: Index) -> Result<&Data> {
+        if self.contains(index) {
+            self.vec.get(index)
+        } else {
+            Err(Error::UnmappedIndex {
+                index: index.into(),
+            })
+        }
+    }
+
+    fn is_occupied(&self, index: Index) -> bool {
+        self.contains(index) && self.vec.is_occupied(index)
+    }
+
+    /// Return the number of distinct indices in the subset that are mapped to an element.
+    fn len(&self) -> usize {
+        self.len
+    }
+}
@@ -0,0 +1,685 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe, RefUnwindSafe, UnwindSafe},
+    rc::Rc,
+};
+
+use crate::{
+    error::Error,
+    implementation::{marked_index::MarkedIndex, non_zero_u32_index::NonZeroU32Index},
+    interface::{StableVec, StableVecAccess, StableVecIndex, StableVecReadAccess},
+};
+
+use super::{Entry, GrowthPolicy, ObservedStableVec, OptionStableVec, ShrinkPolicy};
+
+#[test]
+fn option_stable_vec_is_covariant() {
+    fn shorten_data<'a>(
+        vec: OptionStableVec<&'static str, usize>,
+    ) -> OptionStableVec<&'a str, usize> {
+        vec
+    }
+    fn shorten_index<'a>(
+        vec: OptionStableVec<usize, MarkedIndex<&'static ()>>,
+    ) -> OptionStableVec<usize, MarkedIndex<&'a ()>> {
+        vec
+    }
+
+    let vec: OptionStableVec<&'static str, usize> = ["a", "b"].into_iter().collect();
+    assert_eq!(shorten_data(vec).len(), 2);
+    assert_eq!(shorten_index(OptionStableVec::new()).len(), 0);
+}
+
+#[test]
+fn option_stable_vec_auto_traits() {
+    fn assert_auto_traits<T: Send + Sync + UnwindSafe + RefUnwindSafe>() {}
+    assert_auto_traits::<OptionStableVec<String, usize>>();
+}
+
+#[test]
+fn observer_is_called_for_every_removal() {
+    let removed = Rc::new(RefCell::new(Vec::new()));
+    let observer = {
+        let removed = Rc::clone(&removed);
+        move |index: usize, element: &usize| removed.borrow_mut().push((index, *element))
+    };
+    let mut vec = ObservedStableVec::new((0..10).map(|i| i * 10).collect(), observer);
+
+    assert_eq!(vec.remove(1).unwrap(), 10);
+    assert!(vec.remove(1).is_err());
+    assert_eq!(vec.take(2), Some(20));
+    vec.retain(|element| *element != 30);
+    vec.retain_mut_with_index(|index, element| {
+        *element += 1;
+        index != 4
+    });
+    assert_eq!(vec.remove_range(5..7).count(), 2);
+    assert_eq!(
+        *removed.borrow(),
+        [(1, 10), (2, 20), (3, 30), (4, 41), (5, 51), (6, 61)]
+    );
+
+    removed.borrow_mut().clear();
+    let index = vec.insert(100);
+    assert_eq!(index, 6);
+    vec.clear();
+    let mut cleared = removed.borrow().clone();
+    cleared.sort_unstable();
+    assert_eq!(cleared, [(0, 1), (6, 100), (7, 71), (8, 81), (9, 91)]);
+    assert!(vec.is_empty());
+    vec.stable_vec().check_invariants().unwrap();
+}
+
+/// An index type that silently truncates indices that do not fit into a `u8`, but declares its bound.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TruncatingIndex(u8);
+
+impl From<usize> for TruncatingIndex {
+    fn from(index: usize) -> Self {
+        Self(index as u8)
+    }
+}
+
+impl From<TruncatingIndex> for usize {
+    fn from(index: TruncatingIndex) -> Self {
+        index.0.into()
+    }
+}
+
+impl StableVecIndex for TruncatingIndex {
+    const MAX_INDEX: usize = u8::MAX as usize;
+}
+
+#[test]
+fn shift_indices_rejects_indices_beyond_the_index_type() {
+    let mut vec: OptionStableVec<u32, TruncatingIndex> = (0..3).collect();
+    assert!(matches!(
+        vec.shift_indices(300),
+        Err(Error::IndexOutOfBounds { index: 302, .. })
+    ));
+    assert_eq!(
+        vec.iter().map(|(index, _)| index.0).collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+
+    vec.shift_indices(250).unwrap();
+    assert_eq!(
+        vec.iter()
+            .map(|(index, &element)| (index.0, element))
+            .collect::<Vec<_>>(),
+        [(250, 0), (251, 1), (252, 2)]
+    );
+    vec.check_invariants().unwrap();
+}
+
+#[test]
+fn shift_indices_rejects_indices_beyond_non_zero_u32_index() {
+    let mut vec: OptionStableVec<u8, NonZeroU32Index> = (0..3).collect();
+    let max_index = u32::MAX as usize - 1;
+    assert!(matches!(
+        vec.shift_indices(u32::MAX as usize),
+        Err(Error::IndexOutOfBounds { index, max_index: bound })
+            if index == u32::MAX as usize + 2 && bound == max_index
+    ));
+    assert!(vec.shift_indices(max_index - 1).is_err());
+
+    vec.shift_indices(2).unwrap();
+    assert_eq!(
+        vec.iter()
+            .map(|(index, &element)| (usize::from(index), element))
+            .collect::<Vec<_>>(),
+        [(2, 0), (3, 1), (4, 2)]
+    );
+}
+
+#[test]
+fn renumber_rejects_huge_indices() {
+    let mut vec: OptionStableVec<u32, usize> = (0..3).collect();
+    for huge_index in [usize::MAX, 1 << 62] {
+        assert!(matches!(
+            vec.renumber(|index| if index == 1 { huge_index } else { index }),
+            Err(Error::IndexOutOfBounds { index, .. }) if index == huge_index
+        ));
+    }
+    assert!(matches!(
+        vec.renumber(|_| 5),
+        Err(Error::OverlappingIndices { index: 5 })
+    ));
+    assert_eq!(vec.iter().collect::<Vec<_>>(), [(0, &0), (1, &1), (2, &2)]);
+
+    vec.renumber(|index| 4 - index).unwrap();
+    assert_eq!(vec.iter().collect::<Vec<_>>(), [(2, &2), (3, &1), (4, &0)]);
+    vec.check_invariants().unwrap();
+}
+
+#[test]
+fn compact_stable_assigns_new_indices_in_ascending_order_of_old_indices() {
+    let mut vec: OptionStableVec<char, usize> = "abcdefg".chars().collect();
+    for index in [0, 2, 3, 6] {
+        vec.remove(index).unwrap();
+    }
+
+    assert_eq!(vec.compact_stable(), [(1, 0), (4, 1), (5, 2)]);
+    assert_eq!(
+        vec.iter().collect::<Vec<_>>(),
+        [(0, &'b'), (1, &'e'), (2, &'f')]
+    );
+    vec.check_invariants().unwrap();
+    assert_eq!(vec.insert('h'), 3);
+    assert_eq!(vec.compact_stable(), [(0, 0), (1, 1), (2, 2), (3, 3)]);
+}
+
+#[test]
+fn debug_does_not_require_the_index_marker_to_implement_debug() {
+    struct Node;
+
+    let vec: OptionStableVec<u32, MarkedIndex<Node>> = (0..2).collect();
+    let debug = format!("{vec:?}");
+    assert!(debug.starts_with("OptionStableVec [(MarkedIndex { index: 0, marker: PhantomData<"));
+    assert!(debug.ends_with("Node> }, 1)]"));
+}
+
+#[test]
+fn try_from_hash_map_fails_instead_of_aborting_on_huge_keys() {
+    let map = HashMap::from([(0, 1u64), (1 << 50, 2)]);
+    assert!(matches!(
+        OptionStableVec::<u64, usize>::try_from(map),
+        Err(Error::AllocationFailed { len }) if len == (1 << 50) + 1
+    ));
+
+    let map = HashMap::from([(0, 1u64), (3, 2)]);
+    let vec = OptionStableVec::<u64, usize>::try_from(map).unwrap();
+    assert_eq!(vec.iter().collect::<Vec<_>>(), [(0, &1), (3, &2)]);
+    vec.check_invariants().unwrap();
+}
+
+#[test]
+fn configuration_methods_can_be_combined() {
+    let mut vec: OptionStableVec<u32, usize> = OptionStableVec::with_capacity(4)
+        .growth_policy(GrowthPolicy::Linear { chunk_size: 4 })
+        .shrink_policy(ShrinkPolicy::BelowLoadFactor { threshold: 0.1 })
+        .max_len(6)
+        .sorted_free_list();
+    assert!(vec.free_list_is_sorted());
+
+    for element in 0..6 {
+        vec.try_insert(element).unwrap();
+    }
+    assert!(matches!(
+        vec.try_insert(6),
+        Err(Error::CapacityExceeded { max: 6 })
+    ));
+    assert_eq!(vec.stats().vec_capacity, 8);
+    vec.check_invariants().unwrap();
+}
+
+#[test]
+fn sorted_free_list_sorts_the_existing_holes() {
+    let mut vec: OptionStableVec<u32, usize> = (0..6).collect();
+    for index in [1, 4, 2] {
+        vec.remove(index).unwrap();
+    }
+
+    let mut vec = vec.sorted_free_list();
+    vec.check_invariants().unwrap();
+    assert_eq!([vec.insert(10), vec.insert(11), vec.insert(12)], [1, 2, 4]);
+    vec.check_invariants().unwrap();
+}
+
+#[test]
+#[should_panic]
+fn max_len_panics_below_the_current_length() {
+    let vec: OptionStableVec<u32, usize> = (0..3).collect();
+    let _ = vec.max_len(2);
+}
+
+#[test]
+fn subset_view_only_grants_access_to_the_subset() {
+    let mut vec: OptionStableVec<char, usize> = "abcde".chars().collect();
+    vec.remove(3).unwrap();
+
+    let indices = [0, 3, 4, 0];
+    let view = vec.subset_view(&indices);
+    assert_eq!(view.len(), 2);
+    assert_eq!(*view.get(0).unwrap(), 'a');
+    assert_eq!(*view.get(4).unwrap(), 'e');
+    assert!(matches!(
+        view.get(1),
+        Err(Error::UnmappedIndex { index: 1 })
+    ));
+    assert!(matches!(
+        view.get(3),
+        Err(Error::UnmappedIndex { index: 3 })
+    ));
+    assert!(view.is_occupied(4) && !view.is_occupied(1) && !view.is_occupied(3));
+
+    fn visible(access: impl StableVecReadAccess<char, usize>) -> String {
+        (0..5).filter_map(|index| access.get(index).ok()).collect()
+    }
+    assert_eq!(visible(&view), "ae");
+    assert_eq!(visible(&vec), "abce");
+}
+
+#[test]
+fn free_list_reuses_the_most_recently_freed_index_first() {
+    let mut vec: OptionStableVec<u32, usize> = (0..6).collect();
+    for index in [1, 4, 2] {
+        vec.remove(index).unwrap();
+    }
+    vec.check_invariants().unwrap();
+
+    assert_eq!(
+        [
+            vec.insert(10),
+            vec.insert(11),
+            vec.insert(12),
+            vec.insert(13)
+        ],
+        [2, 4, 1, 6]
+    );
+    vec.check_invariants().unwrap();
+}
+
+#[test]
+fn sorted_free_list_does_not_depend_on_the_order_of_removals() {
+    for removals in [[1, 4, 2], [4, 2, 1], [2, 1, 4]] {
+        let mut vec: OptionStableVec<u32, usize> =
+            (0..6).collect::<OptionStableVec<_, _>>().sorted_free_list();
+        for index in removals {
+            vec.remove(index).unwrap();
+            vec.check_invariants().unwrap();
+        }
+        vec.insert_at_arbitrary_index(8, 8).unwrap();
+        vec.check_invariants().unwrap();
+        vec.remove_range(5..6).for_each(drop);
+        vec.check_invariants().unwrap();
+
+        let indices: Vec<_> = (0..6).map(|element| vec.insert(element)).collect();
+        assert_eq!(indices, [1, 2, 4, 5, 6, 7]);
+        vec.check_invariants().unwrap();
+    }
+}
+
+#[test]
+fn insertions_fail_once_the_maximum_length_is_reached() {
+    let mut vec: OptionStableVec<u32, usize> = OptionStableVec::with_max_len(2);
+    assert_eq!(vec.try_insert(0).unwrap(), 0);
+    vec.insert_at_arbitrary_index(3, 3).unwrap();
+    assert!(matches!(
+        vec.try_insert(1),
+        Err(Error::CapacityExceeded { max: 2 })
+    ));
+    assert!(matches!(
+        vec.insert_at_arbitrary_index(1, 1),
+        Err(Error::CapacityExceeded { max: 2 })
+    ));
+    vec.check_invariants().unwrap();
+
+    vec.remove(0).unwrap();
+    assert_eq!(vec.try_insert(1).unwrap(), 0);
+    vec.check_invariants().unwrap();
+}
+
+#[test]
+fn swap_remove_range_fills_the_range_with_the_last_elements() {
+    let mut vec: OptionStableVec<char, usize> = "abcdefgh".chars().collect();
+    vec.remove(6).unwrap();
+
+    let (removed, remapping) = vec.swap_remove_range(1..3);
+    assert_eq!(removed, ['b', 'c']);
+    assert_eq!(remapping, [(7, 1), (5, 2)]);
+    assert_eq!(vec.iter().map(|(_, &c)| c).collect::<String>(), "ahfde");
+    assert_eq!(vec.stats().index_bound, 5);
+    vec.check_invariants().unwrap();
+
+    vec.remove(1).unwrap();
+    let (removed, remapping) = vec.swap_remove_range(0..2);
+    assert_eq!(removed, ['a']);
+    assert_eq!(remapping, [(4, 0), (3, 1)]);
+    assert_eq!(vec.iter().map(|(_, &c)| c).collect::<String>(), "edf");
+    vec.check_invariants().unwrap();
+
+    let (removed, remapping) = vec.swap_remove_range(2..10);
+    assert_eq!(removed, ['f']);
+    assert!(remapping.is_empty());
+    vec.check_invariants().unwrap();
+}
+
+#[test]
+fn get_disjoint_mut_opt_skips_holes_and_rejects_overlapping_indices() {
+    let mut vec: OptionStableVec<u32, usize> = (0..3).collect();
+    vec.remove(1).unwrap();
+
+    let [first, hole, beyond, last] = vec.get_disjoint_mut_opt([0, 1, 7, 2]).unwrap();
+    assert_eq!(
+        (first, hole, beyond, last),
+        (Some(&mut 0), None, None, Some(&mut 2))
+    );
+    assert!(matches!(
+        vec.get_disjoint_mut_opt([2, 0, 2]),
+        Err(Error::OverlappingIndices { index: 2 })
+    ));
+}
+
+#[test]
+fn check_invariants_holds_after_each_operation() {
+    let mut vec: OptionStableVec<u32, usize> = (0..8).collect();
+    vec.check_invariants().unwrap();
+    vec.insert_at_arbitrary_index(12, 12).unwrap();
+    vec.check_invariants().unwrap();
+    vec.remove(3).unwrap();
+    vec.check_invariants().unwrap();
+    vec.retain(|element| element % 2 == 0);
+    vec.check_invariants().unwrap();
+    assert_eq!(vec.remove_range(4..9).count(), 2);
+    vec.check_invariants().unwrap();
+    vec.reserve_index_range(1..4);
+    vec.check_invariants().unwrap();
+    vec.shift_indices(2).unwrap();
+    vec.check_invariants().unwrap();
+    vec.compact_stable();
+    vec.check_invariants().unwrap();
+    assert_eq!(
+        vec.iter().map(|(_, &element)| element).collect::<Vec<_>>(),
+        [0, 0, 2, 0, 12]
+    );
+    vec.insert_block(vec![20, 21]);
+    vec.check_invariants().unwrap();
+    vec.trim_trailing_holes();
+    vec.check_invariants().unwrap();
+    vec.clear();
+    vec.check_invariants().unwrap();
+    assert!(vec.is_empty());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn retain_par_is_identical_to_retain_with_a_shrink_policy() {
+    let build = || {
+        let mut vec: OptionStableVec<u32, usize> = OptionStableVec::with_capacity(32)
+            .shrink_policy(ShrinkPolicy::BelowLoadFactor { threshold: 0.5 });
+        for element in 0..16 {
+            vec.insert(element);
+        }
+        vec
+    };
+
+    let mut sequential = build();
+    sequential.retain(|&element| element < 9);
+    let mut parallel = build();
+    parallel.retain_par(|&element| element < 9);
+
+    assert_eq!(parallel.stats(), sequential.stats());
+    assert_eq!(parallel.stats().index_bound, 9);
+    assert_eq!(
+        parallel
+            .available_insertion_index_iterator()
+            .take(3)
+            .collect::<Vec<_>>(),
+        sequential
+            .available_insertion_index_iterator()
+            .take(3)
+            .collect::<Vec<_>>()
+    );
+    parallel.check_invariants().unwrap();
+}
+
+#[test]
+fn fill_holes_with_stays_consistent_if_the_constructor_panics() {
+    let mut vec: OptionStableVec<usize, usize> = (0..6).collect();
+    for index in [4, 1, 2] {
+        vec.remove(index).unwrap();
+    }
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        vec.fill_holes_with(|index| {
+            assert_ne!(index, 2);
+            index * 10
+        })
+    }));
+    assert!(result.is_err());
+    vec.check_invariants().unwrap();
+    assert_eq!(vec.len(), 4);
+    assert_eq!(*vec.get(1).unwrap(), 10);
+    assert!(!vec.is_occupied(2));
+
+    vec.fill_holes_with(|index| index * 10);
+    vec.check_invariants().unwrap();
+    assert_eq!(
+        vec.iter().map(|(_, &element)| element).collect::<Vec<_>>(),
+        [0, 10, 20, 3, 40, 5]
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip_keeps_holes_and_trailing_holes() {
+    let mut vec: OptionStableVec<String, usize> = ["a", "b", "c", "d", "e", "f"]
+        .map(String::from)
+        .into_iter()
+        .collect();
+    for index in [1, 3, 4, 5] {
+        vec.remove(index).unwrap();
+    }
+
+    let json = serde_json::to_string(&vec).unwrap();
+    assert_eq!(
+        json,
+        r#"{"version":1,"index_bound":6,"elements":[[0,"a"],[2,"c"]]}"#
+    );
+    let deserialized: OptionStableVec<String, usize> = serde_json::from_str(&json).unwrap();
+    deserialized.check_invariants().unwrap();
+    assert_eq!(deserialized, vec);
+    assert_eq!(deserialized.stats().index_bound, 6);
+    let mut free_indices: Vec<_> = deserialized.free_indices().collect();
+    free_indices.sort_unstable();
+    assert_eq!(free_indices, [1, 3, 4, 5]);
+
+    let reordered: OptionStableVec<String, usize> =
+        serde_json::from_str(r#"{"version":1,"elements":[[2,"c"],[0,"a"]],"index_bound":6}"#)
+            .unwrap();
+    assert_eq!(reordered, vec);
+    let compact: OptionStableVec<String, usize> =
+        serde_json::from_str(r#"[1,6,[[0,"a"],[2,"c"]]]"#).unwrap();
+    assert_eq!(compact, vec);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_rejects_invalid_data() {
+    fn error(json: &str) -> String {
+        serde_json::from_str::<OptionStableVec<u64, usize>>(json)
+            .unwrap_err()
+            .to_string()
+    }
+
+    assert!(
+        error(r#"{"version":1,"index_bound":2,"elements":[[0,1],[0,2]]}"#)
+            .contains("index 0 is duplicated")
+    );
+    assert!(error(r#"{"version":1,"index_bound":2,"elements":[[2,1]]}"#)
+        .contains("index 2 is out of bounds of the index bound 2"));
+    assert!(error(r#"{"version":2,"layout":"unknown"}"#)
+        .contains("unsupported format version 2, expected 1"));
+    assert!(error(r#"[2,"unknown"]"#).contains("unsupported format version 2, expected 1"));
+    assert!(error(r#"{"index_bound":0,"version":1,"elements":[]}"#)
+        .contains("expected the field `version` first"));
+    assert!(
+        error(r#"{"version":1,"index_bound":1000000000000,"elements":[]}"#)
+            .contains("cannot allocate the index bound 1000000000000")
+    );
+}
+
+#[test]
+fn shared_references_implement_read_access() {
+    fn sum(vec: impl StableVecReadAccess<u32, usize>, indices: &[usize]) -> u32 {
+        assert!(!vec.is_empty());
+        indices
+            .iter()
+            .filter_map(|&index| vec.get(index).ok())
+            .sum::<u32>()
+            + vec.len() as u32
+    }
+
+    let mut vec: OptionStableVec<u32, usize> = (0..4).collect();
+    vec.remove(2).unwrap();
+    assert_eq!(sum(&vec, &[0, 1, 2, 3]), 4 + 3);
+}
+
+#[test]
+fn merge_sorted_with_remapping_keeps_the_configuration_of_self() {
+    let mut left: OptionStableVec<u32, usize> = [1, 4, 6, 9]
+        .into_iter()
+        .collect::<OptionStableVec<_, _>>()
+        .max_len(8)
+        .growth_policy(GrowthPolicy::Linear { chunk_size: 3 })
+        .sorted_free_list();
+    left.remove(1).unwrap();
+    let mut right: OptionStableVec<u32, usize> = [0, 4, 5, 7, 10].into_iter().collect();
+    right.remove(3).unwrap();
+
+    let (mut merged, left_remapping, right_remapping) =
+        left.merge_sorted_with_remapping(right, Ord::cmp);
+    assert_eq!(
+        merged
+            .iter()
+            .map(|(_, &element)| element)
+            .collect::<Vec<_>>(),
+        [0, 1, 4, 5, 6, 9, 10]
+    );
+    assert_eq!(left_remapping, [(0, 1), (2, 4), (3, 5)]);
+    assert_eq!(right_remapping, [(0, 0), (1, 2), (2, 3), (4, 6)]);
+    merged.check_invariants().unwrap();
+
+    assert!(merged.free_list_is_sorted());
+    merged.try_insert(11).unwrap();
+    assert!(matches!(
+        merged.try_insert(12),
+        Err(Error::CapacityExceeded { max: 8 })
+    ));
+}
+
+#[test]
+fn replace_with_passes_the_element_by_value() {
+    let mut vec: OptionStableVec<String, usize> =
+        ["a", "b"].map(String::from).into_iter().collect();
+    vec.remove(1).unwrap();
+
+    vec.replace_with(0, |element| element + "c").unwrap();
+    assert_eq!(vec.get(0).unwrap(), "ac");
+    assert!(matches!(
+        vec.replace_with(1, |_| unreachable!()),
+        Err(Error::UnmappedIndex { index: 1 })
+    ));
+    vec.check_invariants().unwrap();
+}
+
+#[test]
+fn entry_or_insert_with_index_fills_a_vacant_index_beyond_the_end() {
+    let mut vec: OptionStableVec<usize, usize> = (0..3).collect();
+
+    assert_eq!(*vec.entry(7).or_insert_with_index(|index| index * 10), 70);
+    vec.check_invariants().unwrap();
+    assert_eq!(vec.len(), 4);
+    assert_eq!(vec.stats().hole_count, 4);
+
+    assert_eq!(
+        *vec.entry(7)
+            .and_modify(|element| *element += 1)
+            .or_insert(0),
+        71
+    );
+    assert_eq!(*vec.entry(5).or_default(), 0);
+    match vec.entry(1) {
+        Entry::Occupied(entry) => assert_eq!(entry.remove(), 1),
+        Entry::Vacant(_) => panic!("index 1 is occupied"),
+    }
+    match vec.entry(1) {
+        Entry::Occupied(_) => panic!("index 1 is vacant"),
+        Entry::Vacant(entry) => assert_eq!(*entry.insert_in_place(|index| index + 100), 101),
+    }
+    vec.check_invariants().unwrap();
+    assert_eq!(
+        vec.iter()
+            .map(|(index, &element)| (index, element))
+            .collect::<Vec<_>>(),
+        [(0, 0), (1, 101), (2, 2), (5, 0), (7, 71)]
+    );
+}
+
+#[test]
+fn intern_keeps_the_first_occurrence_of_each_element() {
+    let mut vec: OptionStableVec<&str, usize> =
+        ["x", "a", "b", "a", "c", "b", "a"].into_iter().collect();
+    vec.remove(0).unwrap();
+
+    let canonical_indices = vec.intern();
+    assert_eq!(canonical_indices, HashMap::from([(3, 1), (5, 2), (6, 1)]));
+    assert_eq!(
+        vec.iter().collect::<Vec<_>>(),
+        [(1, &"a"), (2, &"b"), (4, &"c")]
+    );
+    vec.check_invariants().unwrap();
+    assert!(vec.intern().is_empty());
+}
+
+#[test]
+fn iter_pairs_yields_each_unordered_pair_once() {
+    let mut vec: OptionStableVec<char, usize> = "abcde".chars().collect();
+    vec.remove(2).unwrap();
+
+    let pairs: Vec<_> = vec
+        .iter_pairs()
+        .map(|((a, &x), (b, &y))| ((a, x), (b, y)))
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            ((0, 'a'), (1, 'b')),
+            ((0, 'a'), (3, 'd')),
+            ((0, 'a'), (4, 'e')),
+            ((1, 'b'), (3, 'd')),
+            ((1, 'b'), (4, 'e')),
+            ((3, 'd'), (4, 'e')),
+        ]
+    );
+
+    let single: OptionStableVec<char, usize> = "a".chars().collect();
+    assert_eq!(single.iter_pairs().count(), 0);
+    assert_eq!(
+        OptionStableVec::<char, usize>::new().iter_pairs().count(),
+        0
+    );
+}
+
+#[test]
+fn iter_windows_skips_holes() {
+    let mut vec: OptionStableVec<u32, usize> = (0..6).collect();
+    vec.remove(2).unwrap();
+    vec.remove(5).unwrap();
+
+    let windows: Vec<Vec<_>> = vec
+        .iter_windows(3)
+        .map(|window| {
+            window
+                .into_iter()
+                .map(|(index, &element)| (index, element))
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        windows,
+        [vec![(0, 0), (1, 1), (3, 3)], vec![(1, 1), (3, 3), (4, 4)],]
+    );
+    assert_eq!(vec.iter_windows(4).count(), 1);
+    assert_eq!(vec.iter_windows(5).count(), 0);
+    assert_eq!(vec.iter_windows(1).count(), 4);
+}
+
+#[test]
+#[should_panic]
+fn iter_windows_panics_for_size_zero() {
+    let vec: OptionStableVec<u32, usize> = (0..2).collect();
+    let _ = vec.iter_windows(0);
+}
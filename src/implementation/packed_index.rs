@@ -0,0 +1,51 @@
+//! An index type that packs two 32-bit sub-indices into a single `usize`.
+
+use crate::interface::StableVecIndex;
+
+/// An index type composed of a `region` and a `local` index within that region, which are packed into a single `usize`.
+/// The region is stored in the upper 32 bits and the local index in the lower 32 bits, such that indices are ordered by region first.
+///
+/// Since the packed value of an index in region `r` is at least `r * 2^32`,
+/// this is mostly useful with stable vectors that do not allocate a slot for each index below the largest one used,
+/// or with region `0` only.
+///
+/// This type is only available on 64-bit targets, where every `usize` can be converted losslessly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedIndex {
+    region: u32,
+    local: u32,
+}
+
+impl PackedIndex {
+    /// Create a packed index from the given region and local index.
+    pub fn new(region: u32, local: u32) -> Self {
+        Self { region, local }
+    }
+
+    /// Return the region of this index.
+    pub fn region(&self) -> u32 {
+        self.region
+    }
+
+    /// Return the local index within the region of this index.
+    pub fn local(&self) -> u32 {
+        self.local
+    }
+}
+
+impl StableVecIndex for PackedIndex {}
+
+impl From<usize> for PackedIndex {
+    fn from(index: usize) -> Self {
+        Self {
+            region: (index >> 32) as u32,
+            local: index as u32,
+        }
+    }
+}
+
+impl From<PackedIndex> for usize {
+    fn from(value: PackedIndex) -> Self {
+        ((value.region as usize) << 32) | value.local as usize
+    }
+}
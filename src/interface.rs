@@ -1,8 +1,17 @@
 //! The interfaces that describe a stable vector.
 
-use std::mem;
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    iter::{self, FusedIterator},
+    mem,
+    ops::Range,
+    process, ptr,
+};
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 
 /// The interface that defines the full functionality of a stable vector.
 pub trait StableVec<Data, Index: StableVecIndex>:
@@ -12,6 +21,13 @@ pub trait StableVec<Data, Index: StableVecIndex>:
     /// Return the index.
     fn insert(&mut self, element: Data) -> Index;
 
+    /// Insert a single element into the stable vector at an arbitrary index.
+    /// Return the index together with a mutable reference to the inserted element.
+    fn insert_ref(&mut self, element: Data) -> (Index, &mut Data) {
+        let index: usize = self.insert(element).into();
+        (index.into(), self.get_mut(index.into()).unwrap())
+    }
+
     /// Insert the default value for a single element into the stable vector at an arbitrary index.
     /// Return the index.
     fn insert_default(&mut self) -> Index
@@ -21,6 +37,26 @@ pub trait StableVec<Data, Index: StableVecIndex>:
         self.insert(Default::default())
     }
 
+    /// Insert elements created by `f` at arbitrary indices until the stable vector contains `new_len` elements.
+    /// The elements are inserted like with [`insert`](StableVec::insert), so holes are reused before the stable vector grows.
+    ///
+    /// If the stable vector already contains at least `new_len` elements, nothing happens, i.e. this never removes elements.
+    fn resize_with(&mut self, new_len: usize, mut f: impl FnMut() -> Data) {
+        while self.len() < new_len {
+            self.insert(f());
+        }
+    }
+
+    /// Insert clones of the given elements at arbitrary indices, like with [`insert`](StableVec::insert).
+    fn extend_from_slice(&mut self, elements: &[Data])
+    where
+        Data: Clone,
+    {
+        for element in elements {
+            self.insert(element.clone());
+        }
+    }
+
     /// Insert a single element into the stable vector by constructing it in place.
     /// This method allows to create the element while already knowing its index.
     /// Returns the index.
@@ -42,6 +78,56 @@ pub trait StableVec<Data, Index: StableVecIndex>:
         element: Data,
     ) -> crate::error::Result<()>;
 
+    /// Inserts a single element into the stable vector at the given index, like [`insert_at_arbitrary_index`](StableVec::insert_at_arbitrary_index),
+    /// but only if the index does not exceed `max_index`.
+    /// Otherwise, an [`Error::IndexOutOfBounds`](crate::error::Error::IndexOutOfBounds) is returned before anything is allocated.
+    ///
+    /// This allows to bound the memory usage when inserting at untrusted indices.
+    fn insert_at_checked(&mut self, index: Index, element: Data, max_index: usize) -> Result<()> {
+        let index: usize = index.into();
+        if index > max_index {
+            return Err(Error::IndexOutOfBounds { index, max_index });
+        }
+        self.insert_at_arbitrary_index(index.into(), element)
+    }
+
+    /// Inserts a single element into the stable vector at the given index by constructing it in place.
+    /// This index may be any index that is not currently in use, and the constructor receives it, such that the element can embed its own index.
+    /// If an index that is already mapped to an element is given, an [`Error::IndexAlreadyInUse`](crate::error::Error::IndexAlreadyInUse) is returned,
+    /// and the constructor is not called.
+    ///
+    /// **WARNING:** this method may be slower than expected, because it may for example need to update a free list.
+    fn insert_in_place_at(
+        &mut self,
+        index: Index,
+        constructor: impl FnOnce(Index) -> Data,
+    ) -> crate::error::Result<()> {
+        let index: usize = index.into();
+        if self.is_occupied(index.into()) {
+            return Err(Error::IndexAlreadyInUse { index });
+        }
+        let element = constructor(index.into());
+        self.insert_at_arbitrary_index(index.into(), element)
+    }
+
+    /// Reserves the given range of indices, such that subsequent insertions do not use it.
+    /// Each index in the range that is not yet mapped to an element is filled with the default value,
+    /// and indices that are already mapped to an element are left untouched.
+    ///
+    /// Since the reserved indices are mapped to elements afterwards, they are not returned by [available_insertion_index_iterator](StableVec::available_insertion_index_iterator),
+    /// until they are removed again.
+    fn reserve_index_range(&mut self, range: Range<usize>)
+    where
+        Data: Default,
+    {
+        for index in range {
+            if !self.is_occupied(index.into()) {
+                self.insert_at_arbitrary_index(index.into(), Default::default())
+                    .unwrap();
+            }
+        }
+    }
+
     /// Sets the index to the given value.
     /// If the index was already mapped to a value, the old value is returned.
     /// Otherwise, the given value is newly inserted.
@@ -57,6 +143,50 @@ pub trait StableVec<Data, Index: StableVecIndex>:
         }
     }
 
+    /// Write the given element to the given index, regardless of whether the index is mapped to an element.
+    /// If it was, the old element is replaced and returned, otherwise the given element is newly inserted and `None` is returned.
+    ///
+    /// This is the same as [`set`](StableVec::set).
+    /// It never returns an error, but panics if the index cannot be inserted at, e.g. because it exceeds the maximum supported index
+    /// (see [`Error::IndexOutOfBounds`](crate::error::Error::IndexOutOfBounds)), or because the stable vector already contains its maximum number of elements.
+    /// Use [`insert_at_checked`](StableVec::insert_at_checked) to bound the index for untrusted input.
+    fn insert_or_replace_at(&mut self, index: Index, element: Data) -> Option<Data> {
+        self.set(index, element)
+    }
+
+    /// Return a mutable reference to the element at the given index, inserting the given element first if the index is not mapped to any element.
+    /// The returned flag is `true` if the element was newly inserted, and `false` if an existing element is returned.
+    fn get_or_insert_at(&mut self, index: Index, element: Data) -> (bool, &mut Data) {
+        let index: usize = index.into();
+        let inserted = !self.is_occupied(index.into());
+        if inserted {
+            self.insert_at_arbitrary_index(index.into(), element)
+                .unwrap();
+        }
+        (inserted, self.get_mut(index.into()).unwrap())
+    }
+
+    /// Replace the elements at the given indices with the given elements.
+    /// If any index is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned,
+    /// and no element is replaced.
+    fn assign_from(&mut self, elements: impl IntoIterator<Item = (Index, Data)>) -> Result<()> {
+        let elements: Vec<(usize, Data)> = elements
+            .into_iter()
+            .map(|(index, element)| (index.into(), element))
+            .collect();
+        if let Some(&(index, _)) = elements
+            .iter()
+            .find(|&&(index, _)| !self.is_occupied(index.into()))
+        {
+            return Err(Error::UnmappedIndex { index });
+        }
+
+        for (index, element) in elements {
+            *self.get_mut(index.into()).unwrap() = element;
+        }
+        Ok(())
+    }
+
     /// Insert multiple elements into the stable vector at arbitrary indices.
     /// The indices are returned as an iterator in the order of the inserted elements.
     ///
@@ -84,10 +214,31 @@ pub trait StableVec<Data, Index: StableVecIndex>:
             .map(|constructor| self.insert_in_place(constructor))
     }
 
+    /// Insert multiple elements into the stable vector at the given indices.
+    /// For each element, the result of inserting it is returned as an iterator in the order of the given elements.
+    /// An insertion fails with an [`Error::IndexAlreadyInUse`](crate::error::Error::IndexAlreadyInUse) if its index is already mapped to an element,
+    /// but this does not affect the other insertions.
+    ///
+    /// **Warning**: the returned iterator must be completely exhausted in order to insert all elements.
+    #[must_use = "this iterator must be completely exhausted in order to insert all given elements"]
+    fn insert_at_iter(
+        &mut self,
+        elements: impl IntoIterator<Item = (Index, Data)>,
+    ) -> impl Iterator<Item = Result<()>> {
+        elements
+            .into_iter()
+            .map(|(index, element)| self.insert_at_arbitrary_index(index, element))
+    }
+
     /// Remove and return the element at the given index.
     /// If the index is not mapped to any element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned.
     fn remove(&mut self, index: Index) -> Result<Data>;
 
+    /// Remove and return the element at the given index, or return `None` if the index is not mapped to any element.
+    fn take(&mut self, index: Index) -> Option<Data> {
+        self.remove(index).ok()
+    }
+
     /// Returns an iterator that iterates over the available insertion indices in this stable vector.
     /// These are the "holes" in the underlying vector,
     /// followed by the indices after the end of the underlying vector.
@@ -96,17 +247,19 @@ pub trait StableVec<Data, Index: StableVecIndex>:
         Index: 'result;
 
     /// Return an iterator over the pairs of (index, element) in this stable vec.
-    fn iter<'this>(&'this self) -> impl 'this + Iterator<Item = (Index, &'this Data)>
+    fn iter<'this>(&'this self) -> impl 'this + FusedIterator<Item = (Index, &'this Data)>
     where
         Data: 'this;
 
     /// Return an iterator over the pairs of (index, element) in this stable vec.
-    fn iter_mut<'this>(&'this mut self) -> impl 'this + Iterator<Item = (Index, &'this mut Data)>
+    fn iter_mut<'this>(
+        &'this mut self,
+    ) -> impl 'this + FusedIterator<Item = (Index, &'this mut Data)>
     where
         Data: 'this;
 
     /// Return an iterator over the elements in this stable vec.
-    fn iter_elements<'this>(&'this self) -> impl 'this + Iterator<Item = &'this Data>
+    fn iter_elements<'this>(&'this self) -> impl 'this + FusedIterator<Item = &'this Data>
     where
         Data: 'this,
     {
@@ -114,7 +267,9 @@ pub trait StableVec<Data, Index: StableVecIndex>:
     }
 
     /// Return an iterator over the elements in this stable vec.
-    fn iter_elements_mut<'this>(&'this mut self) -> impl 'this + Iterator<Item = &'this mut Data>
+    fn iter_elements_mut<'this>(
+        &'this mut self,
+    ) -> impl 'this + FusedIterator<Item = &'this mut Data>
     where
         Data: 'this,
     {
@@ -122,40 +277,610 @@ pub trait StableVec<Data, Index: StableVecIndex>:
     }
 
     /// Return an iterator over the indices that are currently valid for this stable vec.
-    fn iter_indices<'this>(&'this self) -> impl 'this + Iterator<Item = Index>
+    fn iter_indices<'this>(&'this self) -> impl 'this + FusedIterator<Item = Index>
     where
         Data: 'this,
     {
         self.iter().map(|(index, _)| index)
     }
 
+    /// Return an iterator over batches of up to `n` pairs of (index, element) in this stable vec, in the order of [`iter`](StableVec::iter).
+    /// All batches contain exactly `n` pairs, except for the last one, which may contain fewer.
+    ///
+    /// Panics if `n` is zero.
+    fn iter_chunks<'this>(
+        &'this self,
+        n: usize,
+    ) -> impl 'this + FusedIterator<Item = Vec<(Index, &'this Data)>>
+    where
+        Data: 'this,
+    {
+        assert_ne!(n, 0, "the chunk size must be non-zero");
+        let mut iter = self.iter();
+        iter::from_fn(move || {
+            let chunk: Vec<_> = iter.by_ref().take(n).collect();
+            (!chunk.is_empty()).then_some(chunk)
+        })
+        .fuse()
+    }
+
+    /// Return an iterator over all windows of `size` consecutive pairs of (index, element) in this stable vec, in the order of [`iter`](StableVec::iter).
+    /// The windows overlap, and holes are skipped, such that consecutive elements are adjacent in a window even if their indices are not.
+    /// If the stable vec contains fewer than `size` elements, the iterator yields nothing.
+    ///
+    /// Panics if `size` is zero.
+    fn iter_windows<'this>(
+        &'this self,
+        size: usize,
+    ) -> impl 'this + FusedIterator<Item = Vec<(Index, &'this Data)>>
+    where
+        Data: 'this,
+    {
+        assert_ne!(size, 0, "the window size must be non-zero");
+        let mut iter = self
+            .iter()
+            .map(|(index, element)| (Into::<usize>::into(index), element));
+        let mut window = VecDeque::with_capacity(size);
+        iter::from_fn(move || {
+            if window.len() == size {
+                window.pop_front();
+            }
+            while window.len() < size {
+                window.push_back(iter.next()?);
+            }
+            Some(
+                window
+                    .iter()
+                    .map(|&(index, element)| (index.into(), element))
+                    .collect(),
+            )
+        })
+        .fuse()
+    }
+
+    /// Return an iterator over all unordered pairs of distinct elements in this stable vec, each as a pair of (index, element).
+    /// The index of the first element of each pair is smaller than that of the second, so for `n` elements, `n * (n - 1) / 2` pairs are yielded.
+    ///
+    /// This collects all pairs of (index, element) into a temporary vector to order them by index.
+    fn iter_pairs<'this>(
+        &'this self,
+    ) -> impl 'this + FusedIterator<Item = ((Index, &'this Data), (Index, &'this Data))>
+    where
+        Data: 'this,
+    {
+        let mut elements: Vec<(usize, &Data)> = self
+            .iter()
+            .map(|(index, element)| (index.into(), element))
+            .collect();
+        elements.sort_unstable_by_key(|&(index, _)| index);
+
+        let mut first = 0;
+        let mut second = 1;
+        iter::from_fn(move || {
+            if second >= elements.len() {
+                first += 1;
+                second = first + 1;
+            }
+            let &(first_index, first_element) = elements.get(first)?;
+            let &(second_index, second_element) = elements.get(second)?;
+            second += 1;
+            Some((
+                (first_index.into(), first_element),
+                (second_index.into(), second_element),
+            ))
+        })
+        .fuse()
+    }
+
+    /// Return an iterator over the pairs of (index, element) in this stable vec, sorted by the given comparison function.
+    /// The sort is stable, i.e. equal elements are yielded in the order of [`iter`](StableVec::iter).
+    ///
+    /// This does not modify the stable vec, but collects all pairs into a temporary vector to sort them.
+    fn iter_sorted_by<'this>(
+        &'this self,
+        mut compare: impl FnMut(&Data, &Data) -> Ordering,
+    ) -> impl 'this + FusedIterator<Item = (Index, &'this Data)>
+    where
+        Data: 'this,
+    {
+        let mut elements: Vec<(usize, &Data)> = self
+            .iter()
+            .map(|(index, element)| (index.into(), element))
+            .collect();
+        elements.sort_by(|(_, a), (_, b)| compare(a, b));
+        elements
+            .into_iter()
+            .map(|(index, element)| (index.into(), element))
+    }
+
+    /// Return an iterator over the pairs of (index, mutable element) in this stable vec, sorted by the given key.
+    /// The sort is stable, i.e. elements with equal keys are yielded in the order of [`iter_mut`](StableVec::iter_mut),
+    /// and the key is computed only once per element.
+    ///
+    /// This does not move any element, but collects all pairs into a temporary vector to sort them.
+    fn iter_mut_sorted_by_key<'this, K: Ord>(
+        &'this mut self,
+        mut key: impl FnMut(&Data) -> K,
+    ) -> impl 'this + FusedIterator<Item = (Index, &'this mut Data)>
+    where
+        Data: 'this,
+    {
+        let mut elements: Vec<(usize, &mut Data)> = self
+            .iter_mut()
+            .map(|(index, element)| (index.into(), element))
+            .collect();
+        elements.sort_by_cached_key(|(_, element)| key(element));
+        elements
+            .into_iter()
+            .map(|(index, element)| (index.into(), element))
+    }
+
+    /// Return the indices that are currently valid for this stable vec, in the order of [`iter`](StableVec::iter).
+    ///
+    /// Unlike [`iter_indices`](StableVec::iter_indices), this does not borrow the stable vec,
+    /// so the elements can be accessed mutably while iterating over the returned indices.
+    fn live_indices(&self) -> Vec<Index> {
+        self.iter_indices().collect()
+    }
+
+    /// Return the number of elements `e` for which `f(&e)` returns `true`.
+    fn count_where(&self, mut f: impl FnMut(&Data) -> bool) -> usize {
+        self.iter_elements().filter(|element| f(element)).count()
+    }
+
+    /// Clear the target vector and fill it with clones of the elements in this stable vec, ordered by index.
+    /// The capacity of the target vector is reused.
+    ///
+    /// The indices of the elements are not preserved, as the target vector is dense.
+    fn clone_into_vec(&self, target: &mut Vec<Data>)
+    where
+        Data: Clone,
+    {
+        target.clear();
+        target.extend(self.iter_elements().cloned());
+    }
+
+    /// Return the name of the index type of this stable vec, as given by [`std::any::type_name`].
+    /// This allows to tell apart stable vecs with different index types when logging or debugging.
+    fn index_type_name(&self) -> &'static str {
+        std::any::type_name::<Index>()
+    }
+
+    /// Count the elements by the given key, returning a map from each key to the number of elements with that key.
+    fn count_by<K: Hash + Eq>(&self, mut key: impl FnMut(&Data) -> K) -> HashMap<K, usize> {
+        let mut counts = HashMap::new();
+        for element in self.iter_elements() {
+            *counts.entry(key(element)).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Call `f` with a mutable reference to each element, skipping holes and leaving all indices unchanged.
+    fn map_in_place(&mut self, f: impl FnMut(&mut Data)) {
+        self.iter_elements_mut().for_each(f);
+    }
+
     /// Remove all elements `e` for which `f(&e)` returns `false`.
     fn retain(&mut self, f: impl FnMut(&Data) -> bool);
 
+    /// Remove all elements `e` for which `f(&e)` returns `false`.
+    /// Return the number of removed elements.
+    fn retain_counted(&mut self, f: impl FnMut(&Data) -> bool) -> usize {
+        let len = self.len();
+        self.retain(f);
+        len - self.len()
+    }
+
+    /// Remove all elements `e` for which `f(&e)` returns `false`.
+    /// Return the indices of the removed elements, in the order in which they were removed.
+    fn retain_collecting_indices(&mut self, mut f: impl FnMut(&Data) -> bool) -> Vec<Index> {
+        let mut removed = Vec::new();
+        self.retain_mut_with_index(|index, element| {
+            let keep = f(element);
+            if !keep {
+                removed.push(index);
+            }
+            keep
+        });
+        removed
+    }
+
+    /// Remove all elements whose index `i` does not satisfy `keep(i)`.
+    /// Unlike [`retain`](StableVec::retain), the decision is based only on the index and not on the element.
+    fn retain_indices(&mut self, keep: impl Fn(Index) -> bool) {
+        let removed: Vec<usize> = self
+            .iter_indices()
+            .map(Into::into)
+            .filter(|&index: &usize| !keep(index.into()))
+            .collect();
+        for index in removed {
+            self.remove(index.into()).unwrap();
+        }
+    }
+
+    /// Remove all elements `e` with index `i` for which `f(i, &mut e)` returns `false`.
+    /// The function may modify the elements, including those that it keeps.
+    fn retain_mut_with_index(&mut self, mut f: impl FnMut(Index, &mut Data) -> bool) {
+        let removed: Vec<usize> = self
+            .iter_mut()
+            .filter_map(|(index, element)| {
+                let index: usize = index.into();
+                (!f(index.into(), element)).then_some(index)
+            })
+            .collect();
+        for index in removed {
+            self.remove(index.into()).unwrap();
+        }
+    }
+
+    /// Remove all elements that are equal to an element with a smaller index, keeping only the first occurrence of each distinct element.
+    /// Return a map from the index of each removed element to the index of the equal element that was kept.
+    fn intern(&mut self) -> HashMap<Index, Index>
+    where
+        Data: Hash + Eq,
+        Index: Hash + Eq,
+    {
+        let mut elements: Vec<(usize, &Data)> = self
+            .iter()
+            .map(|(index, element)| (index.into(), element))
+            .collect();
+        elements.sort_unstable_by_key(|&(index, _)| index);
+
+        let mut canonical_indices = HashMap::with_capacity(elements.len());
+        let duplicates: Vec<(usize, usize)> = elements
+            .into_iter()
+            .filter_map(|(index, element)| {
+                let canonical_index = *canonical_indices.entry(element).or_insert(index);
+                (canonical_index != index).then_some((index, canonical_index))
+            })
+            .collect();
+
+        duplicates
+            .into_iter()
+            .map(|(index, canonical_index)| {
+                self.remove(index.into()).unwrap();
+                (index.into(), canonical_index.into())
+            })
+            .collect()
+    }
+
+    /// Move the elements to the smallest indices, such that the stable vector contains no holes afterwards.
+    /// The new indices are assigned in ascending order of the old indices, i.e. the element with the smallest old index gets the smallest new index,
+    /// and so on, such that the relative order of the elements is preserved.
+    ///
+    /// Return the pairs of (old index, new index) for all elements, ordered by new index.
+    fn compact_stable(&mut self) -> Vec<(Index, Index)>;
+
     /// Delete all elements from the stable vector.
     fn clear(&mut self);
 }
 
-/// The interface that describes methods to access elements inside a stable vector.
+/// The interface that describes methods to read elements inside a stable vector.
 ///
-/// This is separate from the [`StableVec`] trait to allow creating views of a stable vector that do not allow insertion or deletion, but still grants mutable access to contained elements.
-pub trait StableVecAccess<Data, Index> {
+/// This is separate from the [`StableVecAccess`] trait to allow creating read-only views of a stable vector,
+/// and to implement it for shared references, such that functions that are generic over it accept `&vec`.
+pub trait StableVecReadAccess<Data, Index> {
     /// Get a reference to the element at the given index.
     /// If the index is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned.
     fn get(&self, index: Index) -> Result<&Data>;
 
-    /// Get a mutable reference to the element at the given index.
+    /// Get a reference to the element at the given index.
+    /// If the index is not mapped to an element, the error returned by `err` for the index is returned.
+    fn get_or_else<E>(
+        &self,
+        index: Index,
+        err: impl FnOnce(usize) -> E,
+    ) -> std::result::Result<&Data, E>
+    where
+        Index: StableVecIndex,
+    {
+        let index: usize = index.into();
+        self.get(index.into()).map_err(|_| err(index))
+    }
+
+    /// Get a borrowed [`Cow`] of the element at the given index, which can be turned into an owned element by cloning it when needed.
     /// If the index is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned.
-    fn get_mut(&mut self, index: Index) -> Result<&mut Data>;
+    fn get_cow(&self, index: Index) -> Result<Cow<'_, Data>>
+    where
+        Data: Clone,
+    {
+        self.get(index).map(Cow::Borrowed)
+    }
+
+    /// Get a clone of the element at the given index, or the default value if the index is not mapped to an element.
+    /// This never inserts an element.
+    fn get_or_default(&self, index: Index) -> Data
+    where
+        Data: Default + Clone,
+    {
+        self.get(index).cloned().unwrap_or_default()
+    }
+
+    /// Get a clone of the element at the given index.
+    /// If the index is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned.
+    fn get_cloned(&self, index: Index) -> Result<Data>
+    where
+        Data: Clone,
+    {
+        self.get(index).cloned()
+    }
+
+    /// Get a copy of the element at the given index.
+    /// If the index is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned.
+    fn get_copied(&self, index: Index) -> Result<Data>
+    where
+        Data: Copy,
+    {
+        self.get(index).copied()
+    }
+
+    /// Get clones of the elements at the given indices, in the order of the given indices.
+    /// Unlike with [`get_disjoint_mut`](StableVecAccess::get_disjoint_mut), the indices may repeat.
+    /// If any index is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned.
+    fn get_many_cloned(&self, indices: &[Index]) -> Result<Vec<Data>>
+    where
+        Index: StableVecIndex + Copy,
+        Data: Clone,
+    {
+        indices
+            .iter()
+            .map(|&index| self.get_cloned(index))
+            .collect()
+    }
+
+    /// Returns true if the given index is mapped to an element.
+    fn is_occupied(&self, index: Index) -> bool {
+        self.get(index).is_ok()
+    }
 
     /// Return the number of elements in the stable vector.
     fn len(&self) -> usize;
 
+    /// Return a lower bound and an optional upper bound on the number of elements in the stable vector, like [`Iterator::size_hint`].
+    /// The bounds are equal if the exact number of elements is cheap to compute, which is the case for all stable vectors of this crate.
+    fn len_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+
     /// Returns true if the stable vector is empty.
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
 }
 
+/// The interface that describes methods to access elements inside a stable vector.
+///
+/// This is separate from the [`StableVec`] trait to allow creating views of a stable vector that do not allow insertion or deletion, but still grants mutable access to contained elements.
+pub trait StableVecAccess<Data, Index>: StableVecReadAccess<Data, Index> {
+    /// Get a mutable reference to the element at the given index.
+    /// If the index is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned.
+    fn get_mut(&mut self, index: Index) -> Result<&mut Data>;
+
+    /// Get a mutable reference to the element at the given index.
+    /// If the index is not mapped to an element, the error returned by `err` for the index is returned.
+    fn get_mut_or_else<E>(
+        &mut self,
+        index: Index,
+        err: impl FnOnce(usize) -> E,
+    ) -> std::result::Result<&mut Data, E>
+    where
+        Index: StableVecIndex,
+    {
+        let index: usize = index.into();
+        self.get_mut(index.into()).map_err(|_| err(index))
+    }
+
+    /// Get mutable references to the elements at the given indices.
+    /// If any index is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned.
+    /// If the indices are not pairwise distinct, an [`Error::OverlappingIndices`](crate::error::Error::OverlappingIndices) is returned.
+    fn get_disjoint_mut<const N: usize>(&mut self, indices: [Index; N]) -> Result<[&mut Data; N]>
+    where
+        Index: StableVecIndex + Copy,
+    {
+        let elements = self.get_disjoint_mut_opt(indices)?;
+        if let Some(position) = elements.iter().position(Option::is_none) {
+            return Err(Error::UnmappedIndex {
+                index: indices[position].into(),
+            });
+        }
+        Ok(elements.map(Option::unwrap))
+    }
+
+    /// Get mutable references to the elements at the three given indices.
+    /// This is a shorthand for [`get_disjoint_mut`](StableVecAccess::get_disjoint_mut) with three indices, with the same errors.
+    fn get3_mut(
+        &mut self,
+        a: Index,
+        b: Index,
+        c: Index,
+    ) -> Result<(&mut Data, &mut Data, &mut Data)>
+    where
+        Index: StableVecIndex + Copy,
+    {
+        let [a, b, c] = self.get_disjoint_mut([a, b, c])?;
+        Ok((a, b, c))
+    }
+
+    /// Get mutable references to the elements at the given indices, each paired with its index.
+    /// This behaves like [`get_disjoint_mut`](StableVecAccess::get_disjoint_mut), with the same errors.
+    fn get_disjoint_mut_indexed<const N: usize>(
+        &mut self,
+        indices: [Index; N],
+    ) -> Result<[(Index, &mut Data); N]>
+    where
+        Index: StableVecIndex + Copy,
+    {
+        let mut elements = self.get_disjoint_mut(indices)?.into_iter();
+        Ok(indices.map(|index| (index, elements.next().unwrap())))
+    }
+
+    /// Get mutable references to the elements at the given indices.
+    /// Indices that are not mapped to an element yield `None` instead of failing the whole call.
+    /// If the indices are not pairwise distinct, an [`Error::OverlappingIndices`](crate::error::Error::OverlappingIndices) is returned.
+    fn get_disjoint_mut_opt<const N: usize>(
+        &mut self,
+        indices: [Index; N],
+    ) -> Result<[Option<&mut Data>; N]>;
+
+    /// Get mutable references to the element at index `a`, and to the element at index `b` if it is given and mapped to an element.
+    /// If `a` is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned.
+    /// If `a` and `b` are equal, an [`Error::OverlappingIndices`](crate::error::Error::OverlappingIndices) is returned.
+    fn get_disjoint_mut_2_opt(
+        &mut self,
+        a: Index,
+        b: Option<Index>,
+    ) -> Result<(&mut Data, Option<&mut Data>)>
+    where
+        Index: StableVecIndex + Copy,
+    {
+        let Some(b) = b else {
+            return Ok((self.get_mut(a)?, None));
+        };
+
+        let [element_a, element_b] = self.get_disjoint_mut_opt([a, b])?;
+        let element_a = element_a.ok_or(Error::UnmappedIndex { index: a.into() })?;
+        Ok((element_a, element_b))
+    }
+
+    /// Call `f` with a mutable reference to the element at index `target` and a reference to the element at index `source`.
+    /// If `target` and `source` are equal, `f` receives a mutable reference to the element and a reference to a clone of it,
+    /// since the same element cannot be borrowed mutably and immutably at the same time.
+    /// If either index is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned and `f` is not called.
+    fn update_from(
+        &mut self,
+        target: Index,
+        source: Index,
+        f: impl FnOnce(&mut Data, &Data),
+    ) -> Result<()>
+    where
+        Index: StableVecIndex + Copy,
+        Data: Clone,
+    {
+        if target.into() == source.into() {
+            let source = self.get(source)?.clone();
+            f(self.get_mut(target)?, &source);
+        } else {
+            let [target, source] = self.get_disjoint_mut([target, source])?;
+            f(target, source);
+        }
+        Ok(())
+    }
+
+    /// Swap the element at the given index in this stable vec with the element at the same index in `other`.
+    /// If the index is not mapped to an element in either stable vec, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned and nothing is swapped.
+    fn swap_data_with(&mut self, other: &mut Self, index: Index) -> Result<()>
+    where
+        Self: Sized,
+        Index: StableVecIndex + Copy,
+    {
+        mem::swap(self.get_mut(index)?, other.get_mut(index)?);
+        Ok(())
+    }
+
+    /// Replace the element at the given index with the result of calling `f` with the element by value.
+    /// If the index is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned and `f` is not called.
+    ///
+    /// Since `f` consumes the old element, it is not returned.
+    /// If `f` panics, the process is aborted, because the index would be left without an element while the panic unwinds.
+    fn replace_with(&mut self, index: Index, f: impl FnOnce(Data) -> Data) -> Result<()> {
+        struct AbortOnUnwind;
+
+        impl Drop for AbortOnUnwind {
+            fn drop(&mut self) {
+                process::abort();
+            }
+        }
+
+        let element = self.get_mut(index)?;
+        let guard = AbortOnUnwind;
+        // SAFETY: the element is moved out and moved back in without any code in between that could observe it, except `f`.
+        // If `f` unwinds, the guard aborts the process before the moved-out element can be dropped twice or observed.
+        unsafe {
+            let old_element = ptr::read(element);
+            ptr::write(element, f(old_element));
+        }
+        mem::forget(guard);
+        Ok(())
+    }
+
+    /// Call `f` with a mutable reference to the element at each of the given indices, in the order of the given indices.
+    /// If the indices are not pairwise distinct, an [`Error::OverlappingIndices`](crate::error::Error::OverlappingIndices) is returned.
+    /// If any index is not mapped to an element, an [`Error::UnmappedIndex`](crate::error::Error::UnmappedIndex) is returned.
+    /// Both errors are detected before `f` is called for the first time.
+    fn for_each_disjoint_mut(
+        &mut self,
+        indices: &[Index],
+        mut f: impl FnMut(Index, &mut Data),
+    ) -> Result<()>
+    where
+        Index: StableVecIndex + Copy,
+    {
+        let mut sorted_indices: Vec<usize> = indices.iter().map(|&index| index.into()).collect();
+        sorted_indices.sort_unstable();
+        if let Some(window) = sorted_indices
+            .windows(2)
+            .find(|window| window[0] == window[1])
+        {
+            return Err(Error::OverlappingIndices { index: window[0] });
+        }
+        if let Some(&index) = indices.iter().find(|&&index| !self.is_occupied(index)) {
+            return Err(Error::UnmappedIndex {
+                index: index.into(),
+            });
+        }
+
+        for &index in indices {
+            f(index, self.get_mut(index).unwrap());
+        }
+        Ok(())
+    }
+}
+
+/// Forward all methods to the referenced stable vector, such that functions that are generic over [`StableVecReadAccess`] accept `&vec`.
+impl<Data, Index, T: StableVecReadAccess<Data, Index> + ?Sized> StableVecReadAccess<Data, Index>
+    for &T
+{
+    fn get(&self, index: Index) -> Result<&Data> {
+        (**self).get(index)
+    }
+
+    fn is_occupied(&self, index: Index) -> bool {
+        (**self).is_occupied(index)
+    }
+
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    fn len_hint(&self) -> (usize, Option<usize>) {
+        (**self).len_hint()
+    }
+
+    fn is_empty(&self) -> bool {
+        (**self).is_empty()
+    }
+}
+
 /// The interface that describes the index type of a stable vector.
-pub trait StableVecIndex: From<usize> + Into<usize> {}
+///
+/// Converting a `usize` into the index type and back must be lossless for all indices that are used.
+/// In debug builds, the stable vector implementations of this crate assert this on insertion and access.
+pub trait StableVecIndex: From<usize> + Into<usize> {
+    /// The largest index that can be converted into the index type and back losslessly.
+    ///
+    /// Methods that compute new indices, like [`OptionStableVec::shift_indices`](crate::implementation::option_vec::OptionStableVec::shift_indices),
+    /// return an [`Error::IndexOutOfBounds`] instead of converting an index beyond this bound.
+    const MAX_INDEX: usize = usize::MAX;
+}
+
+/// Assert in debug builds that converting the given index into the index type and back is lossless.
+/// This catches misbehaving [`StableVecIndex`] implementations, which would otherwise silently corrupt a stable vector.
+pub(crate) fn debug_assert_lossless_index<Index: StableVecIndex>(index: usize) {
+    debug_assert_eq!(
+        Into::<usize>::into(Index::from(index)),
+        index,
+        "converting the index {index} into {} and back is lossy",
+        std::any::type_name::<Index>()
+    );
+}